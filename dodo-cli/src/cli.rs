@@ -0,0 +1,678 @@
+//! Command-line interface definitions
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use clap::{Args, Parser, Subcommand};
+use dodo_internals::{
+    chrono::{Datelike, Duration, NaiveDate, Weekday},
+    utils::{parse_duration, parse_word, MAX_DAY_OFFSET},
+    Condition, DueTime, Frequency, Priority, Status,
+};
+
+#[derive(Parser, Debug)]
+#[clap(name = "dodo", about = "A plain-text daily to-do list")]
+pub struct Opts {
+    /// What to do. Opens today's tasks in an editor if absent.
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+    /// Acts as if it were the given day, as YYYY-MM-DD,
+    /// instead of today. Only today and past days can be used
+    #[clap(long, global = true)]
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Adds a new task to today's list
+    Add(AddArgs),
+    /// Lists today's tasks
+    List(ListArgs),
+    /// Lists the unfinished tasks that keep getting carried over
+    /// to the next day
+    Stale {
+        /// Only list tasks carried over more than this many days
+        #[clap(short, long, default_value_t = 3)]
+        days: u32,
+        /// Only list tasks that meet all of the given conditions,
+        /// as in `dodo list`
+        query: Vec<Condition>,
+    },
+    /// Marks a task as done
+    Done {
+        /// The index of the task, or a prefix of its ID
+        task: String,
+    },
+    /// Marks a task as not done
+    Undone {
+        /// The index of the task, or a prefix of its ID
+        task: String,
+    },
+    /// Marks a task as cancelled
+    Cancel {
+        /// The index of the task, or a prefix of its ID
+        task: String,
+    },
+    /// Changes the status of a task
+    Mark {
+        /// The index of the task, or a prefix of its ID
+        task: String,
+        /// The new status: todo, in-progress, blocked, waiting,
+        /// cancelled or done
+        #[clap(value_parser = parse_status)]
+        status: Status,
+    },
+    /// Checks a checkbox off
+    Check {
+        /// The task followed by the position of the checkbox in
+        /// its checklist, such as "3.2" or "3.2.1" for nested
+        /// checkboxes
+        checkbox: CheckboxRef,
+    },
+    /// Unchecks a checkbox
+    Uncheck {
+        /// The task followed by the position of the checkbox in
+        /// its checklist, such as "3.2" or "3.2.1" for nested
+        /// checkboxes
+        checkbox: CheckboxRef,
+    },
+    /// Moves a checkbox to another position of the same
+    /// checklist
+    MvItem {
+        /// The checkbox to move, such as "3.4"
+        from: CheckboxRef,
+        /// Where the checkbox should end up, such as "3.1"
+        to: CheckboxRef,
+    },
+    /// Hides a task from the list until a later day
+    Snooze {
+        /// The index of the task, or a prefix of its ID
+        task: String,
+        /// The day the task comes back on: a date as YYYY-MM-DD,
+        /// "tomorrow", a weekday such as "monday", or a number
+        /// of days such as "3days". "today" wakes the task up
+        #[clap(short, long)]
+        until: RelativeDate,
+    },
+    /// Removes a task from today's list
+    Rm {
+        /// The index of the task, or a prefix of its ID
+        task: String,
+    },
+    /// Opens today's tasks in an editor
+    Edit,
+    /// Shows a single task along with its checklist
+    Show {
+        /// The index of the task, or a prefix of its ID
+        task: String,
+    },
+    /// Starts timing the work on a task, stopping any other
+    /// timer
+    Start {
+        /// The index of the task, or a prefix of its ID
+        task: String,
+    },
+    /// Stops the running timer
+    Stop,
+    /// Shows the most urgent task that can be worked on right
+    /// away
+    Next {
+        /// Only consider tasks that meet all of the given
+        /// conditions, as in `dodo list`
+        query: Vec<Condition>,
+    },
+    /// Compares how long tasks took to how long they were
+    /// expected to take, across every day dodo was used on
+    Report,
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Only list tasks that meet all of the given conditions:
+    /// "priority:high", "status:done" or "status:pending",
+    /// "due:today", "due.before:+7d", "due.after:2026-10-20",
+    /// "#tag" or "tag:tag", "+project", "@context",
+    /// "name~text", or "key:value" for custom attributes.
+    /// Conditions starting with '!' are negated
+    pub query: Vec<Condition>,
+    /// Only list open tasks whose dependencies are all done
+    #[clap(long)]
+    pub ready: bool,
+    /// List snoozed tasks as well
+    #[clap(short, long)]
+    pub all: bool,
+    /// Sort tasks by the value of the given custom attribute,
+    /// leaving the ones without it at the end
+    #[clap(short, long)]
+    pub sort: Option<String>,
+    /// Search the tasks of every day dodo was used on, as they
+    /// were on the last day they were seen
+    #[clap(long, conflicts_with_all = &["ready", "all"])]
+    pub history: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// The name of the task
+    #[clap(required = true)]
+    pub name: Vec<String>,
+    /// The priority of the task (high, medium or low)
+    #[clap(
+        short,
+        long,
+        default_value = "medium",
+        value_parser = parse_priority
+    )]
+    pub priority: Priority,
+    /// The due date of the task, as YYYY-MM-DD, optionally
+    /// followed by a time and a timezone, as in
+    /// 2026-10-20T15:00 or 2026-10-20T15:00+02:00
+    #[clap(short, long, value_parser = parse_due)]
+    pub due: Option<(NaiveDate, Option<DueTime>)>,
+    /// How long the task is expected to take, such as "1h30m",
+    /// "2h" or "45m"
+    #[clap(long, value_parser = parse_estimate)]
+    pub estimate: Option<Duration>,
+    /// How often the task comes back once done: "day", "3days",
+    /// weekdays such as "mon,thu", or "month:15"
+    #[clap(short, long, value_parser = parse_frequency)]
+    pub every: Option<Frequency>,
+    /// The last day the task may come back on, as YYYY-MM-DD
+    #[clap(long, requires = "every")]
+    pub until: Option<NaiveDate>,
+    /// A task that must be done before this one can start, given
+    /// by index or ID prefix. May be given more than once
+    #[clap(short, long)]
+    pub after: Vec<String>,
+    /// A tag for the task. May be given more than once
+    #[clap(short, long = "tag", value_parser = parse_word)]
+    pub tags: Vec<String>,
+    /// The project the task is a part of
+    #[clap(long, value_parser = parse_word)]
+    pub project: Option<String>,
+    /// Where the task can be done
+    #[clap(long, value_parser = parse_word)]
+    pub context: Option<String>,
+    /// A custom attribute of the task, as "key:value". May be
+    /// given more than once
+    #[clap(long = "attr", value_parser = parse_attribute)]
+    pub attributes: Vec<(String, String)>,
+}
+
+/// A checkbox given by the task it belongs to (by index or ID
+/// prefix) and its position in each level of the checklist,
+/// counting from 1. Written as "3.2", or "3.2.1" for nested
+/// checkboxes.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CheckboxRef {
+    pub task: String,
+    pub path: Vec<usize>,
+}
+
+impl CheckboxRef {
+    /// The position of the checklist that holds this checkbox,
+    /// within the task's checklist.
+    pub fn parent(&self) -> &[usize] {
+        &self.path[..self.path.len() - 1]
+    }
+
+    /// The position of this checkbox within its own checklist.
+    pub fn position(&self) -> usize {
+        self.path[self.path.len() - 1]
+    }
+}
+
+impl FromStr for CheckboxRef {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            format!(
+                "invalid checkbox '{input}', expected a task \
+                 followed by the position of the checkbox, such \
+                 as '3.2' or '3.2.1'"
+            )
+        };
+
+        let mut parts = input.split('.');
+        let task = parts.next().filter(|task| !task.is_empty());
+        let path = parts
+            .map(|position| match position.parse() {
+                Ok(0) | Err(_) => Err(error()),
+                Ok(position) => Ok(position),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match task {
+            Some(task) if !path.is_empty() => Ok(Self {
+                task: task.to_owned(),
+                path,
+            }),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl Display for CheckboxRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.task)?;
+        for position in &self.path {
+            write!(f, ".{position}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A day given either as is or relative to the current one.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RelativeDate {
+    /// The given date, written as YYYY-MM-DD
+    On(NaiveDate),
+    /// Some days after the current one, written as "3days" or
+    /// "3d". "today" and "tomorrow" are 0 and 1 days. There
+    /// can be up to [`MAX_DAY_OFFSET`] of them
+    InDays(u32),
+    /// The next given weekday after the current day, written as
+    /// "monday" or "mon"
+    Next(Weekday),
+}
+
+impl RelativeDate {
+    /// The date this refers to, counting from `today`.
+    pub fn resolve(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            RelativeDate::On(date) => date,
+            RelativeDate::InDays(days) => {
+                today + Duration::days(days.into())
+            }
+            RelativeDate::Next(weekday) => {
+                let mut date = today.succ();
+                while date.weekday() != weekday {
+                    date = date.succ();
+                }
+                date
+            }
+        }
+    }
+}
+
+impl FromStr for RelativeDate {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let lowercase = input.to_ascii_lowercase();
+
+        let days = lowercase
+            .strip_suffix("days")
+            .or_else(|| lowercase.strip_suffix('d'))
+            .and_then(|days| days.parse().ok())
+            .filter(|&days| i64::from(days) <= MAX_DAY_OFFSET);
+
+        match lowercase.as_str() {
+            "today" => Ok(RelativeDate::InDays(0)),
+            "tomorrow" => Ok(RelativeDate::InDays(1)),
+            _ => days
+                .map(RelativeDate::InDays)
+                .or_else(|| input.parse().ok().map(RelativeDate::On))
+                .or_else(|| {
+                    lowercase.parse().ok().map(RelativeDate::Next)
+                })
+                .ok_or_else(|| {
+                    format!(
+                        "invalid date '{input}', expected \
+                         YYYY-MM-DD, today, tomorrow, a weekday \
+                         such as monday, or a number of days such \
+                         as 3days"
+                    )
+                }),
+        }
+    }
+}
+
+fn parse_priority(input: &str) -> Result<Priority, String> {
+    input.to_lowercase().parse().map_err(|_| {
+        format!(
+            "invalid priority '{input}', expected one of: high, \
+             medium, low"
+        )
+    })
+}
+
+fn parse_status(input: &str) -> Result<Status, String> {
+    input.parse().map_err(|_| {
+        format!(
+            "invalid status '{input}', expected one of: todo, \
+             in-progress, blocked, waiting, cancelled, done"
+        )
+    })
+}
+
+fn parse_due(
+    input: &str,
+) -> Result<(NaiveDate, Option<DueTime>), String> {
+    let (date, time) = match input.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (input, None),
+    };
+
+    let date = date.parse().ok();
+    let time = time.map(str::parse).transpose().ok();
+
+    date.zip(time).ok_or_else(|| {
+        format!(
+            "invalid due date '{input}', expected YYYY-MM-DD, \
+             optionally followed by a time such as T15:00 or \
+             T15:00+02:00"
+        )
+    })
+}
+
+fn parse_estimate(input: &str) -> Result<Duration, String> {
+    parse_duration(input).ok_or_else(|| {
+        format!(
+            "invalid estimate '{input}', expected hours and \
+             minutes such as 1h30m, 2h or 45m"
+        )
+    })
+}
+
+fn parse_frequency(input: &str) -> Result<Frequency, String> {
+    input.parse().map_err(|_| {
+        format!(
+            "invalid frequency '{input}', expected one of: day, \
+             a number of days such as 3days, weekdays such as \
+             mon,thu, or a day of the month such as month:15"
+        )
+    })
+}
+
+/// Splits custom attributes into their key and their value,
+/// which only get validated against the configured schema.
+fn parse_attribute(
+    input: &str,
+) -> Result<(String, String), String> {
+    match input.split_once(':') {
+        Some((key, value))
+            if !key.is_empty() && !value.is_empty() =>
+        {
+            Ok((key.to_owned(), value.to_owned()))
+        }
+        _ => Err(format!(
+            "invalid attribute '{input}', expected a key and a \
+             value such as ticket:ENG-123"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser};
+    use dodo_internals::{
+        chrono::{Duration, NaiveDate},
+        Condition, Priority, Status,
+    };
+
+    use super::{
+        AddArgs, CheckboxRef, Command, ListArgs, Opts,
+        RelativeDate,
+    };
+
+    #[test]
+    fn cli_is_well_formed() {
+        Opts::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_add() {
+        let opts = Opts::parse_from([
+            "dodo",
+            "add",
+            "Write",
+            "report",
+            "--priority",
+            "HIGH",
+            "--due",
+            "2026-10-20",
+        ]);
+
+        match opts.command {
+            Some(Command::Add(AddArgs {
+                name,
+                priority,
+                due,
+                ..
+            })) => {
+                assert_eq!(name.join(" "), "Write report");
+                assert_eq!(priority, Priority::High);
+                let (date, time) = due.unwrap();
+                assert_eq!(date.to_string(), "2026-10-20");
+                assert_eq!(time, None);
+            }
+            other => panic!("expected add, got {other:?}"),
+        }
+
+        let opts = Opts::parse_from([
+            "dodo",
+            "add",
+            "Call",
+            "--due",
+            "2026-10-20T15:00+02:00",
+        ]);
+        match opts.command {
+            Some(Command::Add(AddArgs { due, .. })) => {
+                let (_, time) = due.unwrap();
+                assert_eq!(
+                    time.unwrap().to_string(),
+                    "15:00+02:00"
+                );
+            }
+            other => panic!("expected add, got {other:?}"),
+        }
+
+        assert!(Opts::try_parse_from([
+            "dodo",
+            "add",
+            "Call",
+            "--due",
+            "2026-10-20T25:00"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_dates() {
+        let opts = Opts::parse_from([
+            "dodo",
+            "list",
+            "--date",
+            "2023-01-02",
+        ]);
+        assert_eq!(
+            opts.date,
+            Some(NaiveDate::from_ymd(2023, 1, 2))
+        );
+        assert!(matches!(opts.command, Some(Command::List(_))));
+
+        let opts =
+            Opts::parse_from(["dodo", "--date", "2023-01-02"]);
+        assert!(opts.command.is_none());
+
+        assert!(Opts::try_parse_from([
+            "dodo",
+            "--date",
+            "yesterday"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_timers() {
+        let opts = Opts::parse_from(["dodo", "start", "3"]);
+        assert!(matches!(
+            opts.command,
+            Some(Command::Start { task }) if task == "3"
+        ));
+
+        let opts = Opts::parse_from([
+            "dodo",
+            "add",
+            "Write",
+            "--estimate",
+            "1h30m",
+        ]);
+        match opts.command {
+            Some(Command::Add(AddArgs { estimate, .. })) => {
+                assert_eq!(
+                    estimate,
+                    Some(Duration::minutes(90))
+                );
+            }
+            other => panic!("expected add, got {other:?}"),
+        }
+
+        assert!(Opts::try_parse_from([
+            "dodo",
+            "add",
+            "Write",
+            "--estimate",
+            "soon"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_relative_dates() {
+        // A Wednesday
+        let today = NaiveDate::from_ymd(2023, 1, 4);
+        let resolve = |input: &str| {
+            input.parse::<RelativeDate>().unwrap().resolve(today)
+        };
+
+        assert_eq!(resolve("today"), today);
+        assert_eq!(resolve("Tomorrow"), today.succ());
+        assert_eq!(
+            resolve("3days"),
+            NaiveDate::from_ymd(2023, 1, 7)
+        );
+        assert_eq!(
+            resolve("monday"),
+            NaiveDate::from_ymd(2023, 1, 9)
+        );
+        assert_eq!(
+            resolve("wed"),
+            NaiveDate::from_ymd(2023, 1, 11)
+        );
+        assert_eq!(
+            resolve("2023-02-01"),
+            NaiveDate::from_ymd(2023, 2, 1)
+        );
+
+        assert!("someday".parse::<RelativeDate>().is_err());
+        assert!("4000000000days"
+            .parse::<RelativeDate>()
+            .is_err());
+        assert!(Opts::try_parse_from(["dodo", "snooze", "3"])
+            .is_err());
+    }
+
+    #[test]
+    fn parses_statuses() {
+        let opts = Opts::parse_from([
+            "dodo",
+            "mark",
+            "3",
+            "in-progress",
+        ]);
+
+        match opts.command {
+            Some(Command::Mark { task, status }) => {
+                assert_eq!(task, "3");
+                assert_eq!(status, Status::InProgress);
+            }
+            other => panic!("expected mark, got {other:?}"),
+        }
+
+        assert!(Opts::try_parse_from([
+            "dodo", "mark", "3", "stuck"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_list_filters() {
+        let opts = Opts::parse_from([
+            "dodo",
+            "list",
+            "+website",
+            "tag:infra",
+        ]);
+
+        match opts.command {
+            Some(Command::List(ListArgs { query, .. })) => {
+                assert_eq!(
+                    query,
+                    [
+                        Condition::Project("website".into()),
+                        Condition::Tag("infra".into())
+                    ]
+                )
+            }
+            other => panic!("expected list, got {other:?}"),
+        }
+
+        let opts = Opts::parse_from([
+            "dodo",
+            "next",
+            "!#errands",
+            "due.before:+7d",
+        ]);
+        match opts.command {
+            Some(Command::Next { query }) => {
+                assert_eq!(query.len(), 2)
+            }
+            other => panic!("expected next, got {other:?}"),
+        }
+
+        assert!(Opts::try_parse_from([
+            "dodo",
+            "list",
+            "priority:urgent"
+        ])
+        .is_err());
+        assert!(Opts::try_parse_from([
+            "dodo",
+            "list",
+            "--history",
+            "--all"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_checkboxes() {
+        let opts =
+            Opts::parse_from(["dodo", "mv-item", "3.4", "3.1"]);
+
+        match opts.command {
+            Some(Command::MvItem { from, to }) => {
+                assert_eq!(from.task, "3");
+                assert_eq!(from.path, [4]);
+                assert_eq!(to.path, [1]);
+            }
+            other => panic!("expected mv-item, got {other:?}"),
+        }
+
+        let nested: CheckboxRef = "4f1c.2.1".parse().unwrap();
+        assert_eq!(nested.task, "4f1c");
+        assert_eq!(nested.parent(), [2]);
+        assert_eq!(nested.position(), 1);
+        assert_eq!(nested.to_string(), "4f1c.2.1");
+
+        for invalid in ["3", "3.", ".2", "3.0", "3.two"] {
+            assert!(invalid.parse::<CheckboxRef>().is_err());
+        }
+    }
+}
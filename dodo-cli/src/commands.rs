@@ -0,0 +1,826 @@
+//! Implementations of each of dodo's subcommands
+
+use dodo_internals::{
+    chrono::{DateTime, FixedOffset, NaiveDate},
+    utils::format_duration,
+    AttributeValue, Checkbox, Checklist, Cycle, Query,
+    Recurrence, Status, Task, TaskSet, TimeReport, Timezone,
+    ValidationIssue,
+};
+
+use crate::{
+    cli::{AddArgs, CheckboxRef, ListArgs, RelativeDate},
+    config::Config,
+    error::ParseError,
+    file_ext::FileExt,
+    files::{self, Bookkeeper},
+    formatting::DateBuffer,
+    parser::{annotated, Parser, COMMENT},
+    Error, Result,
+};
+
+/// Finds the index of the task referred to by `query`, which
+/// is either the index of the task or a prefix of its ID.
+fn resolve(tasks: &TaskSet, query: &str) -> Result<usize> {
+    let by_index =
+        query.parse().ok().and_then(|idx| tasks.get(idx));
+    if let Some(task) = by_index {
+        return Ok(task.idx);
+    }
+
+    let mut candidates = tasks.with_id_prefix(query);
+    match (candidates.next(), candidates.next()) {
+        (Some(task), None) => Ok(task.idx),
+        (Some(_), Some(_)) => {
+            Err(Error::AmbiguousTask(query.into()))
+        }
+        (None, _) => Err(Error::NoSuchTask(query.into())),
+    }
+}
+
+/// Adds a new task to today's task set.
+pub fn add(
+    bookkeeper: &mut Bookkeeper,
+    config: &Config,
+    args: AddArgs,
+) -> Result<()> {
+    let mut tasks = bookkeeper.today_taskset()?;
+
+    let name = args.name.join(" ").trim().to_owned();
+    let mut task =
+        Task::new(name, args.priority, bookkeeper.today());
+    if let Some((due_date, due_time)) = args.due {
+        task.due_date = Some(due_date);
+        task.due_time = due_time;
+    }
+    task.estimate = args.estimate;
+    task.recurrence = args.every.map(|frequency| Recurrence {
+        frequency,
+        until: args.until,
+    });
+    task.tags = args.tags.into_iter().collect();
+    task.project = args.project;
+    task.context = args.context;
+    task.attributes = args
+        .attributes
+        .into_iter()
+        .map(|(key, value)| {
+            let value = config
+                .attributes
+                .parse(&key, &value)
+                .map_err(Error::InvalidAttribute)?;
+            Ok((key, value))
+        })
+        .collect::<Result<_>>()?;
+    task.depends_on = args
+        .after
+        .iter()
+        .map(|query| {
+            let idx = resolve(&tasks, query)?;
+            Ok(tasks.get(idx).expect("resolved tasks exist").id)
+        })
+        .collect::<Result<_>>()?;
+
+    let id = task.id;
+    let idx = tasks.push(task);
+    save_checked(bookkeeper, &mut tasks)?;
+
+    println!("Added task {idx} ({})", id.short());
+
+    Ok(())
+}
+
+/// Prints the tasks of today's task set that match the query,
+/// and only the ones that can be worked on right away if
+/// `ready` is set. Snoozed tasks are left out unless `all` is
+/// set. Tasks get sorted by the custom attribute named `sort`,
+/// if any.
+///
+/// Searches every day's task set instead if `history` is set.
+pub fn list(
+    bookkeeper: &Bookkeeper,
+    config: &Config,
+    args: ListArgs,
+) -> Result<()> {
+    let ListArgs {
+        query,
+        ready,
+        all,
+        sort,
+        history,
+    } = args;
+    let query = Query(query);
+
+    if let Some(key) = sort
+        .as_deref()
+        .filter(|key| config.attributes.kind(key).is_none())
+    {
+        return Err(Error::InvalidAttribute(format!(
+            "there's no attribute named '{key}' to sort by"
+        )));
+    }
+
+    if history {
+        return list_history(
+            bookkeeper,
+            &query,
+            sort.as_deref(),
+        );
+    }
+
+    let tasks = bookkeeper.today_taskset()?;
+    let today = bookkeeper.today();
+
+    let is_ready = |task: &Task| {
+        !task.status.is_closed() && !tasks.is_blocked(task)
+    };
+    let (mut listed, snoozed): (Vec<_>, Vec<_>) = tasks
+        .query(&query, today)
+        .filter(|task| !ready || is_ready(task))
+        .partition(|task| all || !task.is_snoozed(today));
+    if let Some(key) = &sort {
+        listed.sort_by_key(|task| by_attribute(task, key));
+    }
+
+    let now = bookkeeper.now();
+    match listed.split_first() {
+        Some((first, rest)) => {
+            print_listed(&tasks, first, now);
+            for task in rest {
+                println!();
+                print_listed(&tasks, task, now);
+            }
+        }
+        None => println!("No tasks to list"),
+    }
+
+    if !snoozed.is_empty() {
+        println!(
+            "\n({} snoozed tasks hidden, use --all to list them)",
+            snoozed.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints the tasks of every day's task set that match `query`,
+/// as they were on the last day they were seen.
+fn list_history(
+    bookkeeper: &Bookkeeper,
+    query: &Query,
+    sort: Option<&str>,
+) -> Result<()> {
+    let history = bookkeeper.history()?;
+
+    let mut found = query.search_history(
+        history.iter().map(|(date, tasks)| (*date, tasks)),
+        bookkeeper.today(),
+    );
+    if let Some(key) = sort {
+        found.sort_by_key(|(_, task)| by_attribute(task, key));
+    }
+
+    match found.split_first() {
+        Some(((date, task), rest)) => {
+            print!("{date} {}. {task}", task.idx);
+            for (date, task) in rest {
+                println!();
+                print!("{date} {}. {task}", task.idx);
+            }
+        }
+        None => println!("No tasks to list"),
+    }
+
+    Ok(())
+}
+
+/// The value tasks get sorted by when sorting by the custom
+/// attribute named `key`, which puts the ones without it last.
+fn by_attribute<'a>(
+    task: &'a Task,
+    key: &str,
+) -> (bool, Option<&'a AttributeValue>) {
+    let value = task.attributes.get(key);
+
+    (value.is_none(), value)
+}
+
+/// Prints the most urgent task matching `query` that can be
+/// worked on right away, as weighed by the configuration.
+pub fn next(
+    bookkeeper: &Bookkeeper,
+    config: &Config,
+    query: Query,
+) -> Result<()> {
+    let tasks = bookkeeper.today_taskset()?;
+    let now = bookkeeper.now();
+
+    match tasks.most_urgent(&query, &config.urgency, now) {
+        Some(task) => {
+            print_listed(&tasks, task, now);
+            println!(
+                "    (urgency {:.1})",
+                tasks.urgency(task, &config.urgency, now)
+            );
+        }
+        None => println!("No tasks can be worked on right now"),
+    }
+
+    Ok(())
+}
+
+/// Prints a task along with whether it's overdue, what it's
+/// waiting on and how long it has been carried over, if that's
+/// the case.
+fn print_listed(
+    tasks: &TaskSet,
+    task: &Task,
+    now: DateTime<FixedOffset>,
+) {
+    print!("{}. {task}", task.idx);
+
+    let today = now.date().naive_local();
+    if task.is_overdue(now) {
+        let deadline =
+            task.deadline(Timezone::from(*now.offset()));
+        if let (Some(due_date), true) = (
+            task.due_date,
+            deadline.is_some_and(|due| due < now),
+        ) {
+            match task.due_time {
+                Some(due_time) => println!(
+                    "    (overdue, was due {due_date} at {due_time})"
+                ),
+                None => println!("    (overdue, was due {due_date})"),
+            }
+        }
+
+        for checkbox in task.checklist.overdue(today) {
+            let due_date = checkbox
+                .due_date()
+                .expect("overdue checkboxes have due dates");
+            println!(
+                "    (overdue checkbox '{}', was due {due_date})",
+                checkbox.description(),
+            );
+        }
+    }
+
+    if tasks.is_blocked(task) {
+        let blockers: Vec<_> = tasks
+            .blockers(task)
+            .map(|blocker| blocker.idx.to_string())
+            .collect();
+        println!("    (blocked by {})", blockers.join(", "));
+    }
+
+    if let Some(wait_until) =
+        task.wait_until.filter(|_| task.is_snoozed(today))
+    {
+        println!("    (snoozed until {wait_until})");
+    }
+
+    if task.carried_over > 0 && !task.status.is_closed() {
+        println!(
+            "    (carried over {}, since {})",
+            format_days(task.carried_over),
+            task.creation_date
+        );
+    }
+}
+
+/// Writes an amount of days, such as "1 day" or "3 days".
+fn format_days(count: u32) -> String {
+    match count {
+        1 => "1 day".into(),
+        count => format!("{count} days"),
+    }
+}
+
+/// Prints the unfinished tasks of today's task set that were
+/// carried over more than the given amount of days and match
+/// `query`.
+pub fn stale(
+    bookkeeper: &Bookkeeper,
+    days: u32,
+    query: Query,
+) -> Result<()> {
+    let tasks = bookkeeper.today_taskset()?;
+    let today = bookkeeper.today();
+
+    let stale: Vec<_> = tasks
+        .stale(days)
+        .into_iter()
+        .filter(|task| query.matches(task, today))
+        .collect();
+    if stale.is_empty() {
+        println!(
+            "No tasks were carried over more than {}",
+            format_days(days)
+        );
+    }
+
+    for task in stale {
+        println!(
+            "{}. {} (carried over {}, since {})",
+            task.idx,
+            task.name,
+            format_days(task.carried_over),
+            task.creation_date
+        );
+    }
+
+    Ok(())
+}
+
+/// Changes the status of a task.
+pub fn set_status(
+    bookkeeper: &mut Bookkeeper,
+    query: &str,
+    status: Status,
+) -> Result<()> {
+    let mut tasks = bookkeeper.today_taskset()?;
+
+    let idx = resolve(&tasks, query)?;
+    let task = tasks.get_mut(idx).expect("resolved tasks exist");
+    task.set_status(status, bookkeeper.now());
+
+    println!("Marked '{}' as {status}", task.name);
+
+    schedule_recurrences(&mut tasks, bookkeeper.today());
+
+    save_checked(bookkeeper, &mut tasks)
+}
+
+/// Checks a checkbox off, or unchecks it.
+pub fn set_checked(
+    bookkeeper: &mut Bookkeeper,
+    reference: &CheckboxRef,
+    is_done: bool,
+) -> Result<()> {
+    let mut tasks = bookkeeper.today_taskset()?;
+
+    let idx = resolve(&tasks, &reference.task)?;
+    let task = tasks.get_mut(idx).expect("resolved tasks exist");
+    let checkbox =
+        checklist_mut(&mut task.checklist, reference.parent())
+            .and_then(|checklist| {
+                checklist.get_mut(reference.position() - 1)
+            })
+            .ok_or_else(|| {
+                Error::NoSuchCheckbox(reference.to_string())
+            })?;
+
+    if checkbox.is_done() != is_done {
+        checkbox.toggle();
+    }
+
+    let verb = if is_done { "Checked" } else { "Unchecked" };
+    println!("{verb} '{}'", checkbox.description());
+
+    save_checked(bookkeeper, &mut tasks)
+}
+
+/// Moves a checkbox to another position of its checklist.
+pub fn move_checkbox(
+    bookkeeper: &mut Bookkeeper,
+    from: &CheckboxRef,
+    to: &CheckboxRef,
+) -> Result<()> {
+    let mut tasks = bookkeeper.today_taskset()?;
+
+    let idx = resolve(&tasks, &from.task)?;
+    if resolve(&tasks, &to.task)? != idx
+        || from.parent() != to.parent()
+    {
+        return Err(Error::DifferentChecklists(
+            from.to_string(),
+            to.to_string(),
+        ));
+    }
+
+    let task = tasks.get_mut(idx).expect("resolved tasks exist");
+    let checklist =
+        checklist_mut(&mut task.checklist, from.parent())
+            .ok_or_else(|| {
+                Error::NoSuchCheckbox(from.to_string())
+            })?;
+    for reference in [from, to] {
+        if reference.position() > checklist.len() {
+            return Err(Error::NoSuchCheckbox(
+                reference.to_string(),
+            ));
+        }
+    }
+
+    checklist.move_item(from.position() - 1, to.position() - 1);
+    println!(
+        "Moved '{}' to {to}",
+        checklist[to.position() - 1].description()
+    );
+
+    save_checked(bookkeeper, &mut tasks)
+}
+
+/// Finds the checklist nested under the checkboxes at the given
+/// positions, counting from 1. An empty path gives `checklist`
+/// itself.
+fn checklist_mut<'a>(
+    checklist: &'a mut Checklist,
+    path: &[usize],
+) -> Option<&'a mut Checklist> {
+    path.iter().try_fold(checklist, |checklist, &position| {
+        checklist
+            .get_mut(position - 1)
+            .map(Checkbox::children_mut)
+    })
+}
+
+/// Hides a task from the list until the given day, or wakes it
+/// up if that day already arrived.
+pub fn snooze(
+    bookkeeper: &mut Bookkeeper,
+    query: &str,
+    until: RelativeDate,
+) -> Result<()> {
+    let mut tasks = bookkeeper.today_taskset()?;
+    let today = bookkeeper.today();
+
+    let idx = resolve(&tasks, query)?;
+    let task = tasks.get_mut(idx).expect("resolved tasks exist");
+    let until = until.resolve(today);
+    task.wait_until = (until > today).then_some(until);
+
+    match task.wait_until {
+        Some(until) => {
+            println!("Snoozed '{}' until {until}", task.name)
+        }
+        None => println!("'{}' is no longer snoozed", task.name),
+    }
+    save_checked(bookkeeper, &mut tasks)
+}
+
+/// Removes a task from today's task set.
+pub fn remove(
+    bookkeeper: &mut Bookkeeper,
+    query: &str,
+) -> Result<()> {
+    let mut tasks = bookkeeper.today_taskset()?;
+
+    let idx = resolve(&tasks, query)?;
+    let task = tasks.remove(idx).expect("resolved tasks exist");
+    save_checked(bookkeeper, &mut tasks)?;
+
+    println!("Removed '{}'", task.name);
+
+    Ok(())
+}
+
+/// Prints a single task, along with how far along its checklist
+/// is.
+pub fn show(bookkeeper: &Bookkeeper, query: &str) -> Result<()> {
+    let tasks = bookkeeper.today_taskset()?;
+
+    let idx = resolve(&tasks, query)?;
+    let task = tasks.get(idx).expect("resolved tasks exist");
+
+    print!("{}. {task}", task.idx);
+
+    if !task.checklist.is_empty() {
+        println!("\nProgress: {}", task.checklist.progress());
+        print_progress(&task.checklist, 1);
+    }
+
+    if !task.time_log.is_empty() {
+        let now = bookkeeper.now();
+        let spent = format_duration(task.time_spent(now));
+        match task.estimate {
+            Some(estimate) => println!(
+                "\nTime spent: {spent} of {}",
+                format_duration(estimate)
+            ),
+            None => println!("\nTime spent: {spent}"),
+        }
+        if let Some(timer) = task.running_timer() {
+            println!("Timer running since {}", timer.start);
+        }
+    }
+
+    Ok(())
+}
+
+/// Starts timing the work on a task, stopping whichever timer
+/// was running before.
+pub fn start(
+    bookkeeper: &mut Bookkeeper,
+    query: &str,
+) -> Result<()> {
+    let mut tasks = bookkeeper.today_taskset()?;
+    let now = bookkeeper.now();
+
+    let idx = resolve(&tasks, query)?;
+    for (stopped, spent) in tasks.start_timer(idx, now) {
+        let task =
+            tasks.get(stopped).expect("stopped tasks exist");
+        println!(
+            "Stopped timing '{}' after {}",
+            task.name,
+            format_duration(spent)
+        );
+    }
+    save_checked(bookkeeper, &mut tasks)?;
+
+    let task = tasks.get(idx).expect("resolved tasks exist");
+    println!("Timing '{}'", task.name);
+
+    Ok(())
+}
+
+/// Stops the running timer.
+pub fn stop(bookkeeper: &mut Bookkeeper) -> Result<()> {
+    let mut tasks = bookkeeper.today_taskset()?;
+    let now = bookkeeper.now();
+
+    let (idx, spent) =
+        tasks.stop_timer(now).ok_or(Error::NoRunningTimer)?;
+    save_checked(bookkeeper, &mut tasks)?;
+
+    let task = tasks.get(idx).expect("stopped tasks exist");
+    println!(
+        "Stopped timing '{}' after {}",
+        task.name,
+        format_duration(spent)
+    );
+
+    Ok(())
+}
+
+/// Prints how long tasks took compared to their estimates, per
+/// task and per day, across every day dodo was used on.
+pub fn report(bookkeeper: &Bookkeeper) -> Result<()> {
+    let history = bookkeeper.history()?;
+    let now = bookkeeper.now();
+
+    let history =
+        history.iter().map(|(date, tasks)| (*date, tasks));
+    print!("{}", TimeReport::new(history, now));
+
+    Ok(())
+}
+
+/// Prints the progress of each checkbox of `checklist` that has
+/// checkboxes of its own, indented by `depth`.
+fn print_progress(checklist: &Checklist, depth: usize) {
+    for checkbox in checklist.iter() {
+        if checkbox.children().is_empty() {
+            continue;
+        }
+
+        println!(
+            "{:indent$}{}: {}",
+            "",
+            checkbox.description(),
+            checkbox.progress(),
+            indent = 4 * depth
+        );
+        print_progress(checkbox.children(), depth + 1);
+    }
+}
+
+/// Lets the user edit today's task set in their editor of choice
+/// and saves the result.
+pub fn edit(
+    bookkeeper: &mut Bookkeeper,
+    config: &Config,
+) -> Result<()> {
+    let task_set = bookkeeper.today_taskset()?;
+
+    let mut text = annotated(&task_set);
+    let mut errors = Vec::new();
+    let parser =
+        Parser::new(&*bookkeeper.clock, &config.attributes);
+
+    let mut edited_tasks = loop {
+        let edited_text = edit::edit(&text)?;
+
+        if !errors.is_empty() && edited_text == text {
+            // The user quit without fixing the errors
+            return Err(Error::Parse(errors));
+        }
+
+        let edited_text = strip_leading_comments(&edited_text);
+
+        match parser.parse(edited_text) {
+            Ok(tasks) => break tasks,
+            Err(Error::Parse(new_errors)) => {
+                eprintln!(
+                    "Could not parse the tasks, reopening the editor"
+                );
+                text = with_error_comments(
+                    edited_text,
+                    &new_errors,
+                );
+                errors = new_errors;
+            }
+            Err(err) => return Err(err),
+        }
+    };
+    warn_about_undeclared_attributes(&edited_tasks, config);
+    edited_tasks.reconcile(&task_set);
+    check(&mut edited_tasks);
+    // The new instances show up as added tasks below
+    edited_tasks.schedule_recurrences(bookkeeper.today());
+
+    let changes = task_set.diff(&edited_tasks);
+    if changes.is_empty() {
+        println!("No changes were made");
+        return Ok(());
+    }
+
+    bookkeeper.save_today(&edited_tasks)?;
+
+    for change in &changes {
+        println!("{change}");
+    }
+    println!("Saved {} tasks", edited_tasks.0.len());
+
+    Ok(())
+}
+
+/// Warns the user about the attributes of `tasks` that aren't
+/// declared in the configuration, whose values can be anything.
+fn warn_about_undeclared_attributes(
+    tasks: &TaskSet,
+    config: &Config,
+) {
+    for task in &tasks.0 {
+        let undeclared = task
+            .attributes
+            .keys()
+            .filter(|key| config.attributes.kind(key).is_none());
+
+        for key in undeclared {
+            eprintln!(
+                "Warning: '{key}' of '{}' isn't declared in the \
+                 configuration, so its value isn't checked",
+                task.name
+            );
+        }
+    }
+}
+
+/// Warns the user about an issue that's left in `tasks`.
+fn report_issue(tasks: &TaskSet, issue: &ValidationIssue) {
+    match issue {
+        ValidationIssue::DependencyCycle(cycle) => eprintln!(
+            "Warning: some tasks depend on each other in a loop \
+             and can never start: {}",
+            describe_cycle(tasks, cycle)
+        ),
+        issue => eprintln!("Warning: {issue}"),
+    }
+}
+
+/// Fixes the issues of `tasks` that can be fixed and warns
+/// about the rest of them.
+fn check(tasks: &mut TaskSet) {
+    for issue in tasks.fix() {
+        println!("Fixed: {issue}");
+    }
+    for issue in tasks.validate() {
+        report_issue(tasks, &issue);
+    }
+}
+
+/// Saves `tasks` as today's task set once they were
+/// [checked](check).
+fn save_checked(
+    bookkeeper: &mut Bookkeeper,
+    tasks: &mut TaskSet,
+) -> Result<()> {
+    check(tasks);
+    bookkeeper.save_today(&*tasks)
+}
+
+/// Lists the names of the tasks in `cycle`.
+fn describe_cycle(tasks: &TaskSet, cycle: &Cycle) -> String {
+    let names: Vec<_> = cycle
+        .0
+        .iter()
+        .filter_map(|&id| tasks.get_by_id(id))
+        .map(|task| format!("'{}'", task.name))
+        .collect();
+
+    names.join(" → ")
+}
+
+/// Creates the next instance of each recurring task that's due
+/// for one and lets the user know about it.
+fn schedule_recurrences(tasks: &mut TaskSet, today: NaiveDate) {
+    for idx in tasks.schedule_recurrences(today) {
+        let task =
+            tasks.get(idx).expect("scheduled tasks exist");
+
+        if let Some(due_date) = task.due_date {
+            println!("Scheduled '{}' for {due_date}", task.name);
+        }
+    }
+}
+
+/// Removes the comment lines at the start of the text, such
+/// as the ones added by [`with_error_comments`].
+fn strip_leading_comments(text: &str) -> &str {
+    let mut rest = text;
+
+    while rest.starts_with(COMMENT) {
+        rest =
+            rest.find('\n').map_or("", |end| &rest[end + 1..]);
+    }
+
+    rest
+}
+
+/// Describes the given errors in comments at the start of the
+/// text, so that the user can fix them.
+fn with_error_comments(
+    text: &str,
+    errors: &[ParseError],
+) -> String {
+    let mut comments = vec![
+        "There are problems with the tasks below.".to_owned(),
+        "Fix them, or quit without saving to discard your changes."
+            .to_owned(),
+    ];
+
+    // Each error takes up two lines
+    let added_lines = comments.len() + 2 * errors.len();
+
+    for error in errors {
+        let line = error.line + added_lines;
+        let column = error.column;
+        comments.push(format!(
+            "Line {line}, column {column}: {}",
+            error.hint
+        ));
+        comments.push(format!("    {}", error.text));
+    }
+
+    let mut annotated = String::new();
+    for comment in comments {
+        annotated.push_str(&format!("{COMMENT} {comment}\n"));
+    }
+    annotated.push_str(text);
+
+    annotated
+}
+
+/// Makes sure there's a task set for today, rolling the tasks
+/// of the last entry over if there's none yet.
+pub fn prepare_today(
+    bookkeeper: &mut Bookkeeper,
+    config: &Config,
+) -> Result<()> {
+    let now = bookkeeper.now();
+    let today = now.date().naive_local();
+
+    // Check if there's already a task file for the current day
+    let file = {
+        let mut buf = DateBuffer::new();
+        let path = buf.format_path(today)?;
+        if today < bookkeeper.last_entry && !path.exists() {
+            // The tasks of the last entry can't be rolled back
+            return Err(Error::NoEntryForDate(today));
+        }
+        files::open_or_create(path)?
+    };
+
+    if !file.is_empty()? {
+        return Ok(());
+    }
+
+    eprintln!("Creating initial file for {today}");
+
+    if bookkeeper.last_entry == today {
+        // Clean slate: there are no tasks to move over to
+        // today!
+        println!("Adding a sample task");
+        let mut tasks = TaskSet(vec![]);
+        tasks.push(crate::sample_task(today));
+        save_checked(bookkeeper, &mut tasks)
+    } else {
+        // We'll move the tasks from the last entry over to the
+        // current entry, as configured
+        let mut rollover =
+            bookkeeper.last_entry_taskset()?.roll_over(
+                &config.rollover,
+                bookkeeper.last_entry,
+                now,
+            );
+        save_checked(bookkeeper, &mut rollover.tasks)?;
+        println!("{rollover}\n");
+        println!("{}", annotated(&rollover.tasks));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+//! Loads the user's configuration file
+
+use dodo_internals::{
+    AttributeSchema, RolloverPolicy, Timezone, UrgencyWeights,
+};
+use fs_err as fs;
+use serde::Deserialize;
+
+use crate::{files, Result};
+
+/// The name of the configuration file, which lives in the
+/// configuration directory of the platform.
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Settings read from the [configuration file](CONFIG_FILE).
+/// Missing settings get their default values.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// What happens to the tasks of the last day when a new
+    /// day starts
+    pub rollover: RolloverPolicy,
+    /// The offset from UTC that decides which day it is and
+    /// which timezone due times are in, such as "+02:00".
+    /// Defaults to the one of the system's clock
+    pub timezone: Option<Timezone>,
+    /// How much each trait of a task weighs when deciding which
+    /// one is the most urgent
+    pub urgency: UrgencyWeights,
+    /// The custom attributes tasks may have and the type of
+    /// their values, such as `ticket = "string"`
+    pub attributes: AttributeSchema,
+}
+
+impl Config {
+    /// Reads the configuration file, if there's one.
+    pub fn load() -> Result<Self> {
+        let dirs = files::project_dirs()?;
+        let path = dirs.config_dir().join(CONFIG_FILE);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(path)?;
+
+        toml::from_str(&text).map_err(Into::into)
+    }
+
+    /// The configured timezone, or the one of the system's clock
+    /// if there's none.
+    pub fn timezone(&self) -> Timezone {
+        self.timezone.unwrap_or_else(Timezone::local)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dodo_internals::{
+        AttributeKind, RolloverPolicy, UrgencyWeights,
+    };
+
+    use super::Config;
+
+    #[test]
+    fn fills_in_missing_settings() {
+        let config: Config = toml::from_str(
+            "[rollover]\ndrop_completed = false\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.rollover,
+            RolloverPolicy {
+                drop_completed: false,
+                ..RolloverPolicy::default()
+            }
+        );
+        assert_eq!(toml::from_str(""), Ok(Config::default()));
+    }
+
+    #[test]
+    fn rejects_unknown_settings() {
+        let config = toml::from_str::<Config>(
+            "[rollover]\ndrop_done = true\n",
+        );
+
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn reads_timezones() {
+        let config: Config =
+            toml::from_str("timezone = \"-03:00\"\n").unwrap();
+
+        assert_eq!(config.timezone(), "-03:00".parse().unwrap());
+        assert!(toml::from_str::<Config>(
+            "timezone = \"Mars\"\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn reads_urgency_weights() {
+        let config: Config = toml::from_str(
+            "[urgency]
+due = 20.0
+age = 0.0
+",
+        )
+        .unwrap();
+
+        assert_eq!(config.urgency.due, 20.0);
+        assert_eq!(config.urgency.age, 0.0);
+        assert_eq!(
+            config.urgency.priority,
+            UrgencyWeights::default().priority
+        );
+    }
+
+    #[test]
+    fn reads_attribute_schemas() {
+        let config: Config = toml::from_str(
+            "[attributes]\nticket = \"string\"\npoints = \"number\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.attributes.kind("points"),
+            Some(AttributeKind::Number)
+        );
+        for invalid in [
+            "[attributes]\nticket = \"text\"\n",
+            "[attributes]\ndue = \"date\"\n",
+        ] {
+            assert!(toml::from_str::<Config>(invalid).is_err());
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{fmt, path::PathBuf};
 
 use dodo_internals::chrono;
 use thiserror::Error as ErrorMacro;
@@ -10,14 +10,82 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("Bincode: {0}")]
     Bincode(#[from] bincode::Error),
+    #[error(
+        "The tasks were saved by a newer version of dodo (format \
+         {0})"
+    )]
+    UnsupportedFormat(u32),
     #[error("No valid home directory was found")]
     NoValidHomeDirFound,
     #[error("Could not create folder '{0}'")]
     CouldNotCreateFolder(PathBuf),
     #[error("The bookkeeping file is invalid")]
     InvalidBookkeepingFile,
+    #[error(
+        "There are no tasks for {0}, which is before the last \
+         day dodo was used on"
+    )]
+    NoEntryForDate(chrono::NaiveDate),
+    #[error("{0} hasn't come yet, so dodo can't be used on it")]
+    FutureDate(chrono::NaiveDate),
+    #[error("There's no task with index or ID '{0}'")]
+    NoSuchTask(String),
+    #[error("More than one task has an ID starting with '{0}'")]
+    AmbiguousTask(String),
+    #[error("There's no checkbox '{0}'")]
+    NoSuchCheckbox(String),
+    #[error(
+        "Checkboxes can only be moved within the same \
+         checklist, and '{0}' and '{1}' are in different ones"
+    )]
+    DifferentChecklists(String, String),
+    #[error("No timer is running")]
+    NoRunningTimer,
+    #[error("Invalid attribute: {0}")]
+    InvalidAttribute(String),
     #[error("Date parsing: {0}")]
     Chrono(#[from] chrono::ParseError),
+    #[error("Invalid configuration file: {0}")]
+    Config(#[from] toml::de::Error),
+    #[error("Could not parse the tasks:\n{}", display_all(.0))]
+    Parse(Vec<ParseError>),
+}
+
+/// A problem found when parsing the text representation of a
+/// task set.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    /// The line where the problem was found, starting at 1
+    pub line: usize,
+    /// The column where the problem was found, starting at 1
+    pub column: usize,
+    /// The contents of the offending line
+    pub text: String,
+    /// What was expected instead
+    pub hint: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            line,
+            column,
+            text,
+            hint,
+        } = self;
+
+        write!(
+            f,
+            "line {line}, column {column}: {hint} (in '{text}')"
+        )
+    }
+}
+
+fn display_all(errors: &[ParseError]) -> String {
+    let lines: Vec<_> =
+        errors.iter().map(ParseError::to_string).collect();
+
+    lines.join("\n")
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -13,14 +13,27 @@ use fs_err as fs;
 use fs_err::{File, OpenOptions};
 
 use directories::ProjectDirs;
-use dodo_internals::{chrono::NaiveDate, utils::today, Task, TaskSet};
+use dodo_internals::{
+    chrono::{DateTime, FixedOffset, NaiveDate},
+    Clock, LegacyTask, Task, TaskSet,
+};
 
+use crate::file_ext::FileExt;
 use crate::formatting::{DateBuffer, FMT_STRING};
 use crate::{Error, Result};
 
+/// Starts every day file written since tasks got identifiers,
+/// which tells them apart from the legacy ones
+const MAGIC: &[u8] = b"dodo";
+/// The version of the format day files are written in, which
+/// comes right after [`MAGIC`]
+const FORMAT_VERSION: u32 = 1;
+
 pub struct Bookkeeper {
     pub bookkeeping_file: File,
     pub last_entry: NaiveDate,
+    /// Decides which day it currently is
+    pub clock: Box<dyn Clock>,
 }
 
 impl Bookkeeper {
@@ -29,7 +42,7 @@ impl Bookkeeper {
     /// date for which we have an entry.
     ///
     /// Assumes the process is currently in the project's data directory.
-    pub fn init() -> Result<Self> {
+    pub fn init(clock: Box<dyn Clock>) -> Result<Self> {
         let mut bookkeeping_file = open_or_create("bookkeeper")?;
 
         let end_pos = bookkeeping_file.seek(SeekFrom::End(0))?;
@@ -39,7 +52,8 @@ impl Bookkeeper {
             // have an entry on is today
             Ok(Self {
                 bookkeeping_file,
-                last_entry: today(),
+                last_entry: clock.today(),
+                clock,
             })
         } else if end_pos % 11 == 0 {
             // All lines in the bookkeeping file must be 11 bytes long (10 bytes for the date and a newline)
@@ -52,6 +66,7 @@ impl Bookkeeper {
             Ok(Self {
                 bookkeeping_file,
                 last_entry: date,
+                clock,
             })
         } else {
             // The bookkeeping file is somehow malformed
@@ -59,13 +74,43 @@ impl Bookkeeper {
         }
     }
 
+    /// The current date and time, according to the bookkeeper's clock
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.clock.now()
+    }
+
+    /// The current date, according to the bookkeeper's clock
+    pub fn today(&self) -> NaiveDate {
+        self.clock.today()
+    }
+
     pub(crate) fn last_entry_taskset(&self) -> Result<TaskSet> {
-        let mut buf = DateBuffer::new();
+        taskset_of(self.last_entry)
+    }
 
-        let path = buf.format_path(self.last_entry)?;
-        let file = File::open(path)?;
+    /// The task list of the day the bookkeeper's clock is on,
+    /// which isn't the last entry when replaying a past day.
+    pub(crate) fn today_taskset(&self) -> Result<TaskSet> {
+        taskset_of(self.today())
+    }
 
-        bincode::deserialize_from(file).map_err(Into::into)
+    /// Reads the task list of every day there's an entry for,
+    /// from the oldest to the newest one, along with its date.
+    pub(crate) fn history(&self) -> Result<Vec<(NaiveDate, TaskSet)>> {
+        let mut dates = Vec::new();
+        for line in BufReader::new(File::open("bookkeeper")?).lines() {
+            let date = NaiveDate::parse_from_str(&line?, FMT_STRING)?;
+            // The same date may have been registered more than once
+            if !dates.contains(&date) {
+                dates.push(date);
+            }
+        }
+        dates.sort_unstable();
+
+        dates
+            .into_iter()
+            .map(|date| Ok((date, taskset_of(date)?)))
+            .collect()
     }
 
     fn append_to_bookkeeping_file(&mut self, date: NaiveDate) -> Result<()> {
@@ -76,28 +121,66 @@ impl Bookkeeper {
         writeln!(self.bookkeeping_file, "{fmt}").map_err(Into::into)
     }
 
-    /// Appends the given entries to today's task list.
+    /// Saves the given entries as today's task list, replacing
+    /// whatever was previously stored for today.
     ///
     /// After this, the current task list will be considered
-    /// the last entry.
-    pub fn append_to_today(&mut self, tasks: impl AsRef<[Task]>) -> Result<()> {
-        let today = today();
+    /// the last entry, unless it's for a day before the last entry.
+    pub fn save_today(&mut self, tasks: impl AsRef<[Task]>) -> Result<()> {
+        let today = self.today();
 
-        if self.last_entry != today {
+        // An empty bookkeeping file means that today's entry
+        // was never registered, even though it's the last entry
+        if self.last_entry < today || self.bookkeeping_file.is_empty()? {
             self.append_to_bookkeeping_file(today)?;
             self.last_entry = today;
         }
 
-        let last_entry_file = {
+        let today_file = {
             let mut buf = DateBuffer::new();
-            let path = buf.format_path(self.last_entry)?;
-            open_or_create(path)?
+            let path = buf.format_path(today)?;
+            File::create(path)?
         };
 
-        bincode::serialize_into(last_entry_file, tasks.as_ref())?;
+        write_tasks(today_file, tasks.as_ref())
+    }
+}
+
+/// Reads the task list stored for `date`.
+fn taskset_of(date: NaiveDate) -> Result<TaskSet> {
+    let mut buf = DateBuffer::new();
+
+    let path = buf.format_path(date)?;
+
+    read_tasks(&fs::read(path)?)
+}
 
-        Ok(())
+/// Writes `tasks` in the current format, after its header.
+fn write_tasks(mut writer: impl Write, tasks: &[Task]) -> Result<()> {
+    writer.write_all(MAGIC)?;
+    bincode::serialize_into(&mut writer, &FORMAT_VERSION)?;
+    bincode::serialize_into(writer, tasks)?;
+
+    Ok(())
+}
+
+/// Reads tasks written by [`write_tasks`], or in the legacy
+/// format, which has no header at all.
+fn read_tasks(bytes: &[u8]) -> Result<TaskSet> {
+    let mut body = match bytes.strip_prefix(MAGIC) {
+        Some(body) => body,
+        None => {
+            let legacy: Vec<LegacyTask> = bincode::deserialize(bytes)?;
+            return Ok(legacy.into());
+        }
+    };
+
+    let version: u32 = bincode::deserialize_from(&mut body)?;
+    if version != FORMAT_VERSION {
+        return Err(Error::UnsupportedFormat(version));
     }
+
+    bincode::deserialize(body).map_err(Into::into)
 }
 
 pub fn open_or_create(path: impl AsRef<Path>) -> Result<File> {
@@ -120,9 +203,14 @@ fn first_line(file: &File) -> Option<String> {
         .flatten()
 }
 
+/// Finds out where dodo's files go on this platform.
+pub fn project_dirs() -> Result<ProjectDirs> {
+    ProjectDirs::from("", "vrmiguel", "dodo").ok_or(Error::NoValidHomeDirFound)
+}
+
 /// Moves the process' current directory to the project's data directory.
 pub fn move_to_data_dir() -> Result<()> {
-    let dirs = ProjectDirs::from("", "vrmiguel", "dodo").ok_or(Error::NoValidHomeDirFound)?;
+    let dirs = project_dirs()?;
 
     let data_dir_did_not_already_exist = dirs.data_dir().exists().not();
 
@@ -137,3 +225,58 @@ pub fn move_to_data_dir() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use dodo_internals::{chrono::NaiveDate, Priority, Status, Task, TaskSet};
+
+    use super::{read_tasks, write_tasks, FORMAT_VERSION, MAGIC};
+    use crate::Error;
+
+    /// A day file written before tasks had identifiers
+    const LEGACY_FILE: &[u8] = include_bytes!("../fixtures/2023-01-02.bin");
+
+    #[test]
+    fn reads_legacy_files() {
+        let tasks = read_tasks(LEGACY_FILE).unwrap();
+
+        let text = tasks.to_string();
+        assert!(text.starts_with("1. [ ] Fill out my tasks [HIGH]"));
+        assert!(text.contains("    * [x] Figure out how to use dodo\n"));
+        assert!(text.contains("    * [ ] Add a task\n"));
+        assert!(text.contains("2. [x] Pay rent [MEDIUM] due:2023-01-05"));
+
+        let rent = &tasks.0[1];
+        assert_eq!(rent.status, Status::Done);
+        assert_eq!(rent.creation_date, NaiveDate::from_ymd(2022, 12, 30));
+        assert_ne!(tasks.0[0].id, rent.id);
+        assert_eq!(tasks.0[0].id.to_string(), "ba743c722470b58d");
+
+        // The same file always gives the same identifiers
+        assert_eq!(read_tasks(LEGACY_FILE).unwrap(), tasks);
+    }
+
+    #[test]
+    fn round_trips_the_current_format() {
+        let mut tasks = TaskSet(vec![]);
+        let today = NaiveDate::from_ymd(2023, 1, 2);
+        tasks.push(Task::new("Buy milk".into(), Priority::Low, today));
+
+        let mut bytes = Vec::new();
+        write_tasks(&mut bytes, &tasks.0).unwrap();
+
+        assert!(bytes.starts_with(MAGIC));
+        assert_eq!(read_tasks(&bytes).unwrap(), tasks);
+    }
+
+    #[test]
+    fn rejects_newer_formats() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend((FORMAT_VERSION + 1).to_le_bytes());
+
+        assert!(matches!(
+            read_tasks(&bytes),
+            Err(Error::UnsupportedFormat(version)) if version == FORMAT_VERSION + 1
+        ));
+    }
+}
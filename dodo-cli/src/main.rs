@@ -1,77 +1,126 @@
-use dodo::{utils::today, Checkbox, Priority, Task};
+use clap::Parser as _;
+use cli::{Command, Opts};
+use config::Config;
+use dodo::{
+    chrono::NaiveDate, Checkbox, Clock, OffsetClock, Priority,
+    Query, Status, SystemClock, Task,
+};
 pub use dodo_internals as dodo;
 pub use error::{Error, Result};
-use file_ext::FileExt;
 use files::Bookkeeper;
-use formatting::DateBuffer;
-use parser::Parser;
 
+mod cli;
+mod commands;
+mod config;
 mod error;
 mod file_ext;
 mod files;
 mod formatting;
 mod parser;
 
-fn run() -> Result<()> {
-    let today = dodo::utils::today();
+fn run(opts: Opts) -> Result<()> {
+    let config = Config::load()?;
 
     files::move_to_data_dir()?;
 
-    // Check if there's already a task file for the current day
-    let file = {
-        let mut buf = DateBuffer::new();
-        let path = buf.format_path(today)?;
-        files::open_or_create(path)?
+    let clock = SystemClock::new(config.timezone());
+    let clock: Box<dyn Clock> = match opts.date {
+        // Using dodo on a future day would make every day
+        // before it look like the past
+        Some(date) if date > clock.today() => {
+            return Err(Error::FutureDate(date))
+        }
+        Some(date) => {
+            Box::new(OffsetClock::on_date(clock, date))
+        }
+        None => Box::new(clock),
     };
+    let mut bookkeeper = Bookkeeper::init(clock)?;
 
-    let mut bookkeeper = Bookkeeper::init()?;
+    commands::prepare_today(&mut bookkeeper, &config)?;
 
-    if file.is_empty()? {
-        eprintln!("Creating initial file for {today}");
-        if bookkeeper.last_entry == today {
-            // Clean slate: there are no tasks to move over to
-            // today!
-            println!("Adding a sample task");
-            bookkeeper.append_to_today(&[sample_task()])?;
-        } else {
-            // We'll move the pending tasks from the last entry
-            // over to the current entry
-            let tasks = bookkeeper.last_entry_taskset()?;
-            bookkeeper.append_to_today(&tasks)?;
-            println!("{tasks}");
+    match opts.command.unwrap_or(Command::Edit) {
+        Command::Add(args) => {
+            commands::add(&mut bookkeeper, &config, args)
+        }
+        Command::List(args) => {
+            commands::list(&bookkeeper, &config, args)
+        }
+        Command::Stale { days, query } => {
+            commands::stale(&bookkeeper, days, Query(query))
+        }
+        Command::Done { task } => commands::set_status(
+            &mut bookkeeper,
+            &task,
+            Status::Done,
+        ),
+        Command::Undone { task } => commands::set_status(
+            &mut bookkeeper,
+            &task,
+            Status::Todo,
+        ),
+        Command::Cancel { task } => commands::set_status(
+            &mut bookkeeper,
+            &task,
+            Status::Cancelled,
+        ),
+        Command::Mark { task, status } => {
+            commands::set_status(&mut bookkeeper, &task, status)
+        }
+        Command::Check { checkbox } => commands::set_checked(
+            &mut bookkeeper,
+            &checkbox,
+            true,
+        ),
+        Command::Uncheck { checkbox } => commands::set_checked(
+            &mut bookkeeper,
+            &checkbox,
+            false,
+        ),
+        Command::MvItem { from, to } => {
+            commands::move_checkbox(&mut bookkeeper, &from, &to)
+        }
+        Command::Snooze { task, until } => {
+            commands::snooze(&mut bookkeeper, &task, until)
+        }
+        Command::Rm { task } => {
+            commands::remove(&mut bookkeeper, &task)
+        }
+        Command::Edit => {
+            commands::edit(&mut bookkeeper, &config)
+        }
+        Command::Show { task } => {
+            commands::show(&bookkeeper, &task)
+        }
+        Command::Start { task } => {
+            commands::start(&mut bookkeeper, &task)
+        }
+        Command::Stop => commands::stop(&mut bookkeeper),
+        Command::Report => commands::report(&bookkeeper),
+        Command::Next { query } => {
+            commands::next(&bookkeeper, &config, Query(query))
         }
     }
-
-    // Get the current task set
-    let task_set = bookkeeper.last_entry_taskset()?;
-    // Let the user edit the task set as he sees fit
-    let edited_text = edit::edit(task_set.to_string())?;
-
-    let edited_tasks = Parser::parse(&edited_text)?;
-
-    println!("{edited_tasks}");
-
-    Ok(())
 }
 
 fn main() {
-    if let Err(err) = run() {
+    if let Err(err) = run(Opts::parse()) {
         println!("Error: {err}");
     }
 }
 
-pub fn sample_task() -> Task {
-    Task {
-        idx: 0,
-        name: "Fill out my tasks".into(),
-        is_done: false,
-        creation_date: today(),
-        due_date: None,
-        priority: Priority::High,
-        checklist: vec![Checkbox::with_description(
-            "Figure out how to use dodo".into(),
-        )]
-        .into_iter()
-        .collect(),
-    }
+pub fn sample_task(today: NaiveDate) -> Task {
+    let mut task = Task::new(
+        "Fill out my tasks".into(),
+        Priority::High,
+        today,
+    );
+    task.idx = 1;
+    task.checklist = vec![Checkbox::with_description(
+        "Figure out how to use dodo".into(),
+    )]
+    .into_iter()
+    .collect();
+
+    task
 }
@@ -1,219 +1,868 @@
+use std::borrow::Cow;
+
 use dodo_internals::{
-    utils::today, Checkbox, Checklist, Priority, Task, TaskSet,
+    chrono::{DateTime, Duration, FixedOffset, NaiveDate},
+    utils::{escape, is_word_char, parse_duration, unescape},
+    AttributeKind, AttributeSchema, AttributeValue, Checkbox,
+    Checklist, Clock, DueTime, Priority, Recurrence, Status,
+    Task, TaskId, TaskSet,
 };
 use nom::{
     branch::alt,
-    bytes::{
-        complete::{tag, take_till, take_while},
-        streaming::take,
+    bytes::complete::{
+        escaped, is_not, tag, take_till, take_while1,
+        take_while_m_n,
+    },
+    character::complete::{
+        anychar, char, line_ending, none_of, space0, space1,
+    },
+    combinator::{
+        cut, eof, map, map_opt, map_res, opt, peek, verify,
     },
-    character::complete::{char, multispace0},
-    error::ParseError,
+    error::{context, VerboseError, VerboseErrorKind},
     multi::many0,
-    sequence::{self, delimited},
-    IResult,
+    sequence::{delimited, pair, preceded, terminated},
+    Offset,
 };
 
-use crate::Result;
+use crate::{
+    error::ParseError, formatting::FMT_STRING, Error, Result,
+};
+
+/// Lines starting with this are ignored by the parser.
+pub const COMMENT: &str = "//";
+
+/// Writes `tasks` the way [`Parser::parse`] reads them, with a
+/// [comment](COMMENT) above each blocked task listing the tasks
+/// it's waiting for, along with their numbers.
+pub fn annotated(tasks: &TaskSet) -> String {
+    let mut text = String::new();
+
+    for (i, task) in tasks.0.iter().enumerate() {
+        let blockers: Vec<_> = tasks
+            .blockers(task)
+            .map(|blocker| {
+                let number = tasks
+                    .0
+                    .iter()
+                    .position(|task| task.id == blocker.id)
+                    .map_or(0, |pos| pos + 1);
+
+                format!("{number}. {}", escape(&blocker.name))
+            })
+            .collect();
+        if !blockers.is_empty() {
+            text.push_str(&format!(
+                "{COMMENT} Blocked by {}\n",
+                blockers.join(", ")
+            ));
+        }
+        text.push_str(&format!("{}. {task}\n", i + 1));
+    }
+
+    text
+}
+
+type IResult<'a, T> =
+    nom::IResult<&'a str, T, VerboseError<&'a str>>;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 struct TaskHeader<'a> {
     idx: u32,
-    is_checked: bool,
-    name: &'a str,
+    status: Status,
+    name: Cow<'a, str>,
     priority: Priority,
+    attributes: Vec<Attribute<'a>>,
+}
+
+/// A piece of metadata that may follow the priority of a task
+/// in its header.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+enum Attribute<'a> {
+    DueDate(NaiveDate, Option<DueTime>),
+    WaitUntil(NaiveDate),
+    Recurrence(Recurrence),
+    Estimate(Duration),
+    Dependency(TaskId),
+    Id(TaskId),
+    Tag(&'a str),
+    Project(&'a str),
+    Context(&'a str),
+    Custom(&'a str, AttributeValue),
+}
+
+/// A piece of metadata that may follow the description of a
+/// checkbox.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+enum CheckboxAttribute<'a> {
+    Priority(Priority),
+    DueDate(NaiveDate),
+    Note(&'a str),
 }
 
 impl TaskHeader<'_> {
     pub fn with_checkboxes(
         self,
         checkboxes: Vec<Checkbox>,
+        now: DateTime<FixedOffset>,
     ) -> Task {
-        Task {
-            idx: self.idx as _,
-            name: self.name.to_owned(),
-            is_done: self.is_checked,
-            // TODO: figure this out
-            creation_date: today(),
-            // TODO: figure this out
-            due_date: None,
-            priority: self.priority,
-            checklist: Checklist::with_checkboxes(checkboxes),
+        // The creation date gets restored by
+        // `TaskSet::reconcile` for tasks that already existed.
+        // Tasks the user just wrote get a new ID.
+        let mut task = Task::new(
+            self.name.into_owned(),
+            self.priority,
+            now.date().naive_local(),
+        );
+        task.idx = self.idx as _;
+        task.set_status(self.status, now);
+        task.checklist = Checklist::with_checkboxes(checkboxes);
+
+        for attribute in self.attributes {
+            match attribute {
+                Attribute::DueDate(date, time) => {
+                    task.due_date = Some(date);
+                    task.due_time = time;
+                }
+                Attribute::WaitUntil(date) => {
+                    task.wait_until = Some(date)
+                }
+                Attribute::Recurrence(recurrence) => {
+                    task.recurrence = Some(recurrence)
+                }
+                Attribute::Estimate(estimate) => {
+                    task.estimate = Some(estimate)
+                }
+                Attribute::Dependency(id) => {
+                    task.depends_on.push(id)
+                }
+                Attribute::Id(id) => task.id = id,
+                Attribute::Tag(tag) => {
+                    task.tags.insert(tag.to_owned());
+                }
+                Attribute::Project(project) => {
+                    task.project = Some(project.to_owned())
+                }
+                Attribute::Context(context) => {
+                    task.context = Some(context.to_owned())
+                }
+                Attribute::Custom(key, value) => {
+                    task.attributes
+                        .insert(key.to_owned(), value);
+                }
+            }
         }
+
+        task
     }
 }
 
-fn ws<'a, F: 'a, O, E: ParseError<&'a str>>(
-    inner: F,
-) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
-where
-    F: Fn(&'a str) -> IResult<&'a str, O, E>,
-{
-    sequence::delimited(multispace0, inner, multispace0)
+/// Skips over whitespace and [comment](COMMENT) lines.
+fn skip_blanks_and_comments(mut input: &str) -> &str {
+    loop {
+        input = input.trim_start();
+
+        if !input.starts_with(COMMENT) {
+            return input;
+        }
+
+        input = input.find('\n').map_or("", |end| &input[end..]);
+    }
 }
 
-fn parse_task(input: &str) -> IResult<&str, Task> {
-    let input = input.trim_start();
+/// Skips over blank and [comment](COMMENT) lines, leaving the
+/// indentation of the first line with anything else in it.
+fn skip_blank_lines(mut input: &str) -> &str {
+    loop {
+        let end = input.find('\n');
+        let line = &input[..end.unwrap_or(input.len())];
+        let line = line.trim();
+
+        if !line.is_empty() && !line.starts_with(COMMENT) {
+            return input;
+        }
 
-    let (rest, header) = parse_task_header(input)?;
+        match end {
+            Some(end) => input = &input[end + 1..],
+            None => return "",
+        }
+    }
+}
+
+/// Parses a task, taking `now` as the moment it was created and
+/// had its status set, and `schema` as the custom attributes it
+/// may have.
+fn parse_task<'a>(
+    input: &'a str,
+    now: DateTime<FixedOffset>,
+    schema: &AttributeSchema,
+) -> IResult<'a, Task> {
+    let input = skip_blanks_and_comments(input);
+
+    let (rest, header) = parse_task_header(input, schema)?;
 
     let (rest, checkboxes) = many0(parse_checkbox)(rest)?;
 
-    Ok((
-        rest.trim_end(),
-        header.with_checkboxes(checkboxes),
-    ))
+    let (rest, notes) = many0(parse_note_line)(rest)?;
+
+    let mut task = header.with_checkboxes(nest(checkboxes), now);
+    task.notes = notes.join("\n");
+
+    // Trimming the rest of the input only once nothing but
+    // whitespace is left keeps the notes of later tasks intact
+    let rest = if rest.trim().is_empty() { "" } else { rest };
+
+    Ok((rest, task))
 }
 
-fn parse_task_header(input: &str) -> IResult<&str, TaskHeader> {
+/// Parses a line of the notes of a task, which starts with a
+/// '>' and goes after the checklist.
+///
+/// Examples: "    > Acceptance criteria:", "    >"
+fn parse_note_line(input: &str) -> IResult<'_, &str> {
+    let input = skip_blank_lines(input);
+
+    let (rest, _marker) = pair(space0, char('>'))(input)?;
+
+    let (rest, _space) = opt(char(' '))(rest)?;
+
+    let (rest, line) = take_till(|ch| ch == '\n')(rest)?;
+
+    Ok((rest, line.strip_suffix('\r').unwrap_or(line)))
+}
+
+fn parse_task_header<'a>(
+    input: &'a str,
+    schema: &AttributeSchema,
+) -> IResult<'a, TaskHeader<'a>> {
     let (rest, idx) = parse_index(input)?;
 
-    let (rest, is_checked) = parse_checkmark(rest)?;
+    let (rest, status) = parse_checkmark(rest)?;
 
-    let (rest, name) = take_till(|ch| ch == '[')(rest)?;
+    let (rest, name) = parse_escaped_name(rest)?;
 
     let (rest, priority) = parse_priority(rest)?;
 
+    let (rest, attributes) = context(
+        "expected an attribute, such as 'due:2022-06-30', or \
+         the end of the line",
+        terminated(
+            many0(preceded(space1, |input| {
+                parse_attribute(input, schema)
+            })),
+            preceded(space0, peek(alt((line_ending, eof)))),
+        ),
+    )(rest)?;
+
     let header = TaskHeader {
         idx,
-        is_checked,
-        name: name.trim(),
+        status,
+        name: unescape(name.trim()),
         priority,
+        attributes,
     };
 
     Ok((rest, header))
 }
 
+/// Parses the name of a task, which ends at its priority.
+/// Brackets within the name must be [escaped](escape).
+///
+/// Examples: "Water the plants ", "Review PR \[#42\] "
+fn parse_escaped_name(input: &str) -> IResult<'_, &str> {
+    let (rest, name) =
+        opt(escaped(is_not("\\[\n"), '\\', none_of("\n")))(
+            input,
+        )?;
+
+    Ok((rest, name.unwrap_or_default()))
+}
+
+/// Parses the [attributes](Attribute) that may follow a task's
+/// priority, including the custom ones declared in `schema`.
+///
+/// Examples: "due:2022-06-30", "due:2022-06-30T15:00", "wait:2022-06-27", "every:mon,thu", "est:1h30m",
+/// "after:4f1c09a2b3d8e7f6", "id:4f1c09a2b3d8e7f6", "#errands", "+website", "@home", "ticket:ENG-123"
+fn parse_attribute<'a>(
+    input: &'a str,
+    schema: &AttributeSchema,
+) -> IResult<'a, Attribute<'a>> {
+    alt((
+        map(
+            preceded(
+                tag("due:"),
+                cut(pair(
+                    parse_date,
+                    opt(preceded(
+                        char('T'),
+                        cut(parse_due_time),
+                    )),
+                )),
+            ),
+            |(date, time)| Attribute::DueDate(date, time),
+        ),
+        map(
+            preceded(tag("wait:"), cut(parse_date)),
+            Attribute::WaitUntil,
+        ),
+        map(
+            preceded(tag("every:"), cut(parse_recurrence)),
+            Attribute::Recurrence,
+        ),
+        map(
+            preceded(tag("est:"), cut(parse_estimate)),
+            Attribute::Estimate,
+        ),
+        map(
+            preceded(tag("after:"), cut(parse_id)),
+            Attribute::Dependency,
+        ),
+        map(preceded(tag("id:"), cut(parse_id)), Attribute::Id),
+        map(
+            preceded(char('#'), cut(parse_word)),
+            Attribute::Tag,
+        ),
+        map(
+            preceded(char('+'), cut(parse_word)),
+            Attribute::Project,
+        ),
+        map(
+            preceded(char('@'), cut(parse_word)),
+            Attribute::Context,
+        ),
+        |input| parse_custom_attribute(input, schema),
+    ))(input)
+}
+
+/// Parses custom attributes. The values of the ones declared in
+/// `schema` must be of the type they were declared with, while
+/// the ones that aren't, such as the ones that were removed from
+/// the configuration, are kept as strings.
+///
+/// Examples: "ticket:ENG-123", "points:3", "review:2022-07-01"
+fn parse_custom_attribute<'a>(
+    input: &'a str,
+    schema: &AttributeSchema,
+) -> IResult<'a, Attribute<'a>> {
+    let is_key_char = |ch: char| {
+        ch.is_ascii_alphanumeric() || ch == '-' || ch == '_'
+    };
+
+    let (rest, key) = verify(
+        terminated(take_while1(is_key_char), char(':')),
+        |key: &str| !AttributeSchema::RESERVED.contains(&key),
+    )(input)?;
+    let kind = schema.kind(key).unwrap_or(AttributeKind::String);
+
+    let hint = match kind {
+        AttributeKind::String => {
+            "expected a value without whitespace or brackets"
+        }
+        AttributeKind::Number => {
+            "expected a number, such as '3' or '0.5'"
+        }
+        AttributeKind::Date => {
+            "expected a date in the YYYY-MM-DD format"
+        }
+        AttributeKind::Duration => {
+            "expected a duration, such as '1h30m', '2h' or '45m'"
+        }
+    };
+    let (rest, value) = cut(context(
+        hint,
+        map_opt(take_while1(is_word_char), |value| {
+            kind.parse(value)
+        }),
+    ))(rest)?;
+
+    Ok((rest, Attribute::Custom(key, value)))
+}
+
+/// Parses the names of tags, projects and contexts, which may
+/// have anything but whitespace and brackets.
+///
+/// Examples: "errands", "website", "home"
+fn parse_word(input: &str) -> IResult<'_, &str> {
+    context(
+        "expected a name without whitespace or brackets",
+        take_while1(is_word_char),
+    )(input)
+}
+
+/// Parses how often a task recurs, optionally followed by when
+/// it stops recurring.
+///
+/// Examples: "day", "3days", "mon,thu", "month:15 until:2022-12-31"
+fn parse_recurrence(input: &str) -> IResult<'_, Recurrence> {
+    let frequency = context(
+        "expected how often the task recurs, such as 'day', \
+         '3days', 'mon,thu' or 'month:15'",
+        map_res(take_while1(is_word_char), str::parse),
+    );
+    let until = opt(preceded(
+        pair(space1, tag("until:")),
+        cut(parse_date),
+    ));
+
+    map(pair(frequency, until), |(frequency, until)| {
+        Recurrence { frequency, until }
+    })(input)
+}
+
+/// Parses how long a task is expected to take, in hours and
+/// minutes.
+///
+/// Examples: "1h30m", "2h", "45m"
+fn parse_estimate(input: &str) -> IResult<'_, Duration> {
+    context(
+        "expected how long the task takes, such as '1h30m', \
+         '2h' or '45m'",
+        map_opt(take_while1(is_word_char), parse_duration),
+    )(input)
+}
+
+/// Parses [task IDs](TaskId), which are made out of up to 16
+/// hex digits.
+///
+/// Examples: "4f1c09a2b3d8e7f6"
+fn parse_id(input: &str) -> IResult<'_, TaskId> {
+    context(
+        "expected a task ID made out of up to 16 hex digits",
+        map_res(
+            take_while_m_n(1, 16, |ch: char| {
+                ch.is_ascii_hexdigit()
+            }),
+            str::parse,
+        ),
+    )(input)
+}
+
+/// Parses the time of day a task is due at, which may be
+/// followed by a timezone.
+///
+/// Examples: "15:00", "09:30+02:00", "18:00Z"
+fn parse_due_time(input: &str) -> IResult<'_, DueTime> {
+    let is_time_char = |ch: char| {
+        ch.is_ascii_digit()
+            || matches!(ch, ':' | '+' | '-' | 'Z')
+    };
+
+    context(
+        "expected a time such as '15:00', optionally followed by \
+         a timezone such as '+02:00'",
+        map_res(take_while1(is_time_char), str::parse),
+    )(input)
+}
+
+/// Parses dates in the YYYY-mm-dd format.
+///
+/// Examples: "2022-06-30"
+fn parse_date(input: &str) -> IResult<'_, NaiveDate> {
+    let is_date_char =
+        |ch: char| ch.is_ascii_digit() || ch == '-';
+
+    context(
+        "expected a date in the YYYY-MM-DD format",
+        map_res(take_while1(is_date_char), |date| {
+            NaiveDate::parse_from_str(date, FMT_STRING)
+        }),
+    )(input)
+}
+
 /// Parses a [`Checkbox`], which consists of an asterisk, a
-/// checkmark and a description.
+/// checkmark and a description, along with the width of its
+/// indentation. Tabs count as four spaces.
 ///
 /// Examples:
 /// '* [x] Finish this doctest'
-fn parse_checkbox(input: &str) -> IResult<&str, Checkbox> {
-    let input = input.trim_start();
+fn parse_checkbox(
+    input: &str,
+) -> IResult<'_, (usize, Checkbox)> {
+    let input = skip_blank_lines(input);
 
-    let (rest, _asterisk) = ws(char('*'))(input)?;
+    let (rest, indentation) = space0(input)?;
+    let width = indentation
+        .chars()
+        .map(|ch| if ch == '\t' { 4 } else { 1 })
+        .sum();
 
-    let (rest, is_checked) = parse_checkmark(rest)?;
+    let (rest, _asterisk) = terminated(char('*'), space0)(rest)?;
 
-    let (rest, description) = take_till(|ch| ch == '\n')(rest)?;
+    // Anything starting with an asterisk must be a checkbox
+    let (rest, is_checked) = cut(parse_checkbox_mark)(rest)?;
 
-    let checkbox =
-        Checkbox::with_description(description.trim().into())
+    let (rest, description) = parse_escaped_name(rest)?;
+
+    let (rest, attributes) = context(
+        "expected a priority, due date or note between \
+         brackets, such as '[HIGH]', '[due:2022-06-30]' or \
+         '[note: call first]', or the end of the line",
+        terminated(
+            many0(preceded(space0, parse_checkbox_attribute)),
+            preceded(space0, peek(alt((line_ending, eof)))),
+        ),
+    )(rest)?;
+
+    let description = unescape(description.trim());
+
+    let mut checkbox =
+        Checkbox::with_description(description.into_owned())
             .with_status(is_checked);
+    for attribute in attributes {
+        checkbox = match attribute {
+            CheckboxAttribute::Priority(priority) => {
+                checkbox.with_priority(priority)
+            }
+            CheckboxAttribute::DueDate(date) => {
+                checkbox.with_due_date(date)
+            }
+            CheckboxAttribute::Note(note) => checkbox
+                .with_note(unescape(note.trim()).into_owned()),
+        };
+    }
 
-    Ok((rest, checkbox))
+    Ok((rest, (width, checkbox)))
 }
 
-/// Parses number tags consisting of a number followed by a dot.
+/// Parses the metadata that may follow the description of a
+/// checkbox, which goes between brackets.
 ///
-/// Examples: "1.", "230."
-fn parse_index(input: &str) -> IResult<&str, u32> {
-    let input = input.trim_start();
+/// Examples: "[HIGH]", "[due:2022-06-30]", "[note: call first]"
+fn parse_checkbox_attribute(
+    input: &str,
+) -> IResult<'_, CheckboxAttribute<'_>> {
+    let due_date = preceded(tag("due:"), cut(parse_date));
+    let note = preceded(
+        tag("note:"),
+        map(
+            opt(escaped(is_not("\\]\n"), '\\', none_of("\n"))),
+            Option::unwrap_or_default,
+        ),
+    );
+
+    alt((
+        map(parse_priority, CheckboxAttribute::Priority),
+        delimited(
+            char('['),
+            alt((
+                map(due_date, CheckboxAttribute::DueDate),
+                map(note, CheckboxAttribute::Note),
+            )),
+            cut(char(']')),
+        ),
+    ))(input)
+}
+
+/// Builds a tree out of checkboxes and the width of their
+/// indentation. Each checkbox goes under the closest checkbox
+/// before it that's indented less.
+fn nest(checkboxes: Vec<(usize, Checkbox)>) -> Vec<Checkbox> {
+    let mut roots = Vec::new();
+    // The checkboxes that may still get children, along with
+    // their indentation and the children they got so far
+    let mut open: Vec<(usize, Checkbox, Vec<Checkbox>)> =
+        Vec::new();
 
-    let is_digit = |chr: char| {
-        let chr = chr as u8;
-        (0x30..=0x39).contains(&chr)
+    let close = |open: &mut Vec<(usize, Checkbox, Vec<_>)>,
+                 roots: &mut Vec<Checkbox>| {
+        let (_, checkbox, children) =
+            open.pop().expect("there's a checkbox to close");
+        let checkbox = checkbox
+            .with_children(Checklist::with_checkboxes(children));
+
+        match open.last_mut() {
+            Some((_, _, siblings)) => siblings.push(checkbox),
+            None => roots.push(checkbox),
+        }
     };
 
-    let (rest, digits) = take_while(is_digit)(input)?;
+    for (width, checkbox) in checkboxes {
+        while open
+            .last()
+            .is_some_and(|(open_width, ..)| *open_width >= width)
+        {
+            close(&mut open, &mut roots);
+        }
+
+        open.push((width, checkbox, Vec::new()));
+    }
+
+    while !open.is_empty() {
+        close(&mut open, &mut roots);
+    }
 
-    // Ensure digits is not empty
-    let (_, _): (&str, &str) = take(1_usize)(digits)?;
+    roots
+}
 
-    let (rest, _) = char('.')(rest)?;
+/// Parses number tags consisting of a number followed by a dot.
+///
+/// Examples: "1.", "230."
+fn parse_index(input: &str) -> IResult<'_, u32> {
+    let input = input.trim_start();
 
-    let number = digits
-        .parse()
-        .expect("Digits somehow are an invalid number");
+    let is_digit = |chr: char| chr.is_ascii_digit();
 
-    Ok((rest, number))
+    context(
+        "expected a task number, such as '1.'",
+        map_res(
+            terminated(take_while1(is_digit), char('.')),
+            str::parse,
+        ),
+    )(input)
 }
 
-/// Parses checkmarks consisting of a "x", "X" or " " within
-/// brackets.
+/// Parses checkmarks consisting of the [mark](Status::mark) of a
+/// status within brackets.
 ///
-/// Examples: "[x]", "[X]", "[ ]"
-fn parse_checkmark(input: &str) -> IResult<&str, bool> {
+/// Examples: "[x]", "[X]", "[ ]", "[~]", "[!]", "[?]", "[-]"
+fn parse_checkmark(input: &str) -> IResult<'_, Status> {
     let input = input.trim_start();
 
-    let (rest, chr) = delimited(
-        char('['),
-        alt((char('X'), char('x'), char(' '))),
-        char(']'),
-    )(input)?;
+    context(
+        "expected a checkmark, such as '[ ]', '[~]' (in progress), \
+         '[!]' (blocked), '[?]' (waiting), '[-]' (cancelled) or \
+         '[x]'",
+        delimited(
+            char('['),
+            map_opt(anychar, Status::from_mark),
+            char(']'),
+        ),
+    )(input)
+}
 
-    let is_checked = matches!(chr, 'x' | 'X');
+/// Parses the checkmarks of checkboxes, which may only be
+/// checked or unchecked.
+///
+/// Examples: "[x]", "[X]", "[ ]"
+fn parse_checkbox_mark(input: &str) -> IResult<'_, bool> {
+    let input = input.trim_start();
+
+    let is_checked = |mark| match Status::from_mark(mark)? {
+        Status::Todo => Some(false),
+        Status::Done => Some(true),
+        _ => None,
+    };
 
-    Ok((rest, is_checked))
+    context(
+        "expected a checkmark, such as '[ ]' or '[x]'",
+        delimited(
+            char('['),
+            map_opt(anychar, is_checked),
+            char(']'),
+        ),
+    )(input)
 }
 
 /// Parses priority declarations.
 ///
 /// Examples: "[HIGH]", "[MEDIUM]", "[LOW]"
-fn parse_priority(input: &str) -> IResult<&str, Priority> {
-    let (rest, priority): (&str, &str) = delimited(
-        tag("["),
-        alt((
-            tag("HIGH"),
-            tag("MEDIUM"),
-            tag("LOW"),
-            tag("high"),
-            tag("medium"),
-            tag("low"),
-        )),
-        tag("]"),
+fn parse_priority(input: &str) -> IResult<'_, Priority> {
+    let (rest, priority): (&str, &str) = context(
+        "expected a priority, such as '[HIGH]', '[MEDIUM]' or \
+         '[LOW]'",
+        delimited(
+            tag("["),
+            alt((
+                tag("HIGH"),
+                tag("MEDIUM"),
+                tag("LOW"),
+                tag("high"),
+                tag("medium"),
+                tag("low"),
+            )),
+            tag("]"),
+        ),
     )(input)?;
 
-    // TODO: error mgmt
-    let priority = Priority::from_str(priority)
-        .expect("invalid priority text");
+    let priority = priority
+        .parse()
+        .expect("the tags above only match valid priorities");
 
     Ok((rest, priority))
 }
 
-pub struct Parser;
+/// Builds a [`ParseError`] out of the error nom gave when
+/// parsing `input`.
+fn diagnose(input: &str, err: VerboseError<&str>) -> ParseError {
+    // The innermost context is the most specific description of
+    // what went wrong
+    let innermost_context = err.errors.iter().find_map(
+        |(location, kind)| match kind {
+            VerboseErrorKind::Context(hint) => {
+                Some((*location, *hint))
+            }
+            _ => None,
+        },
+    );
+
+    let (location, hint) = innermost_context.unwrap_or((
+        err.errors.first().map_or("", |(location, _)| location),
+        "could not make sense of this line",
+    ));
+
+    let before = &input[..input.offset(location)];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let text = input[line_start..].lines().next().unwrap_or("");
+
+    ParseError {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        text: text.trim().to_owned(),
+        hint,
+    }
+}
+
+/// Returns the input starting at the first line after `line`
+/// that looks like the beginning of a task.
+fn skip_to_next_task(input: &str, line: usize) -> &str {
+    let mut rest = input;
+
+    for (current, text) in
+        input.split_inclusive('\n').enumerate()
+    {
+        if current + 1 > line && parse_index(text).is_ok() {
+            return rest;
+        }
+        rest = &rest[text.len()..];
+    }
+
+    rest
+}
+
+pub struct Parser<'a> {
+    /// Tells when the parsed tasks were created
+    clock: &'a dyn Clock,
+    /// The custom attributes the parsed tasks may have
+    schema: &'a AttributeSchema,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(
+        clock: &'a dyn Clock,
+        schema: &'a AttributeSchema,
+    ) -> Self {
+        Self { clock, schema }
+    }
 
-impl Parser {
-    pub fn parse(input: &str) -> Result<TaskSet> {
+    /// Parses a task set out of its text representation.
+    ///
+    /// Lines starting with [`COMMENT`] are ignored. Parsing
+    /// carries on after a malformed task so that all of the
+    /// problems in the input get reported at once.
+    pub fn parse(&self, input: &str) -> Result<TaskSet> {
         // This function reimplements nom's many0 because it
         // somehow behaves incorrectly here
+        let now = self.clock.now();
         let mut tasks = Vec::new();
-        let mut rest = input;
+        let mut errors = Vec::new();
+        let mut rest = skip_blanks_and_comments(input);
 
-        loop {
-            match parse_task(rest) {
+        while !rest.is_empty() {
+            match parse_task(rest, now, self.schema) {
                 Ok((new_rest, task)) => {
                     tasks.push(task);
                     rest = new_rest;
                 }
-                Err(nom::Err::Incomplete(_)) => break,
-                Err(err) => {
-                    eprintln!("Parsing problem: {err}");
-                    // TODO: reopen editor to allow user to fix
-                    // the mistake?
-                    break;
+                Err(
+                    nom::Err::Error(err)
+                    | nom::Err::Failure(err),
+                ) => {
+                    let error = diagnose(input, err);
+                    rest = skip_to_next_task(input, error.line);
+                    errors.push(error);
+                }
+                Err(nom::Err::Incomplete(_)) => {
+                    unreachable!(
+                        "only complete parsers are used"
+                    )
                 }
             }
+
+            rest = skip_blanks_and_comments(rest);
         }
 
-        Ok(TaskSet(tasks))
+        if errors.is_empty() {
+            Ok(TaskSet(tasks))
+        } else {
+            Err(Error::Parse(errors))
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
     use dodo_internals::{
-        utils::today, Checkbox, Priority, Task, TaskSet,
+        chrono::{
+            DateTime, Duration, FixedOffset, NaiveDate,
+            NaiveTime, TimeZone, Weekday,
+        },
+        AttributeKind, AttributeSchema, AttributeValue,
+        Checkbox, Clock, DueTime, FixedClock, Frequency,
+        Priority, Recurrence, Status, Task, TaskId, TaskSet,
+        Timezone,
+    };
+    use proptest::{
+        collection::{btree_map, btree_set, vec},
+        option,
+        prelude::*,
+        sample::select,
     };
 
     use super::{
-        parse_checkmark, parse_index, parse_task_header, Parser,
+        annotated, parse_checkmark, parse_date, parse_index,
+        parse_task_header, Parser,
     };
-    use crate::parser::{
-        parse_checkbox, parse_priority, parse_task, TaskHeader,
+    use crate::{
+        error::ParseError,
+        parser::{
+            parse_checkbox, parse_priority, parse_task,
+            Attribute, TaskHeader,
+        },
+        Error,
     };
 
+    /// The moment the tests take place at
+    fn clock() -> FixedClock {
+        FixedClock::new(
+            FixedOffset::east(0)
+                .ymd(2023, 1, 2)
+                .and_hms(9, 0, 0),
+        )
+    }
+
+    fn now() -> DateTime<FixedOffset> {
+        clock().now()
+    }
+
+    fn today() -> NaiveDate {
+        clock().today()
+    }
+
+    /// The custom attributes the tests' tasks may have
+    fn schema() -> AttributeSchema {
+        let mut schema = AttributeSchema::default();
+        for (key, kind) in [
+            ("ticket", AttributeKind::String),
+            ("points", AttributeKind::Number),
+            ("review", AttributeKind::Date),
+            ("spent", AttributeKind::Duration),
+        ] {
+            schema.declare(key, kind).unwrap();
+        }
+
+        schema
+    }
+
+    fn parse(input: &str) -> crate::Result<TaskSet> {
+        Parser::new(&clock(), &schema()).parse(input)
+    }
+
+    fn id(hex: &str) -> TaskId {
+        hex.parse().unwrap()
+    }
+
     #[test]
     fn parses_number_tags() {
         assert_eq!(parse_index("5."), Ok(("", 5)));
@@ -225,9 +874,26 @@ mod tests {
 
     #[test]
     fn parses_checkmark() {
-        assert_eq!(parse_checkmark("[x]"), Ok(("", true)));
-        assert_eq!(parse_checkmark("[X]"), Ok(("", true)));
-        assert_eq!(parse_checkmark("[ ]"), Ok(("", false)));
+        assert_eq!(
+            parse_checkmark("[x]"),
+            Ok(("", Status::Done))
+        );
+        assert_eq!(
+            parse_checkmark("[X]"),
+            Ok(("", Status::Done))
+        );
+        assert_eq!(
+            parse_checkmark("[ ]"),
+            Ok(("", Status::Todo))
+        );
+        assert_eq!(
+            parse_checkmark("[~]"),
+            Ok(("", Status::InProgress))
+        );
+        assert_eq!(
+            parse_checkmark("[-]"),
+            Ok(("", Status::Cancelled))
+        );
 
         assert!(parse_checkmark("[]").is_err());
         assert!(parse_checkmark("{x}").is_err());
@@ -239,10 +905,13 @@ mod tests {
             parse_checkbox("  * [x] Finish this test"),
             Ok((
                 "",
-                Checkbox::with_description(
-                    "Finish this test".into()
+                (
+                    2,
+                    Checkbox::with_description(
+                        "Finish this test".into()
+                    )
+                    .with_status(true)
                 )
-                .with_status(true)
             ))
         );
 
@@ -250,12 +919,111 @@ mod tests {
             parse_checkbox("*[ ] Finish this test\n"),
             Ok((
                 "\n",
-                Checkbox::with_description(
-                    "Finish this test".into()
+                (
+                    0,
+                    Checkbox::with_description(
+                        "Finish this test".into()
+                    )
+                    .with_status(false)
                 )
-                .with_status(false)
             ))
         );
+
+        assert!(
+            parse_checkbox("* [~] Finish this test").is_err()
+        );
+    }
+
+    #[test]
+    fn parses_checkbox_attributes() {
+        let (_, (_, checkbox)) = parse_checkbox(
+            "* [ ] Book flights \\[window\\] [HIGH] \
+             [due:2022-06-30] [note: ask about \\[bags\\]]",
+        )
+        .unwrap();
+
+        assert_eq!(
+            checkbox.description(),
+            "Book flights [window]"
+        );
+        assert_eq!(checkbox.priority(), Some(Priority::High));
+        assert_eq!(
+            checkbox.due_date(),
+            Some(NaiveDate::from_ymd(2022, 6, 30))
+        );
+        assert_eq!(checkbox.note(), Some("ask about [bags]"));
+        assert_eq!(
+            checkbox.to_string(),
+            "[ ] Book flights \\[window\\] [HIGH] \
+             [due:2022-06-30] [note: ask about \\[bags\\]]"
+        );
+
+        assert!(
+            parse_checkbox("* [ ] Book flights [soon]").is_err()
+        );
+        assert!(parse_checkbox("* [ ] Book flights [due:June]")
+            .is_err());
+    }
+
+    #[test]
+    fn parses_nested_checkboxes() {
+        let text = "1. [ ] Write report [HIGH] id:0000000000000001\n    * [ ] Research\n        * [x] Find sources\n\t\t* [ ] Take notes\n            * [x] Summarize\n    * [ ] Draft\n";
+
+        let tasks = parse(text).unwrap();
+        let checklist = &tasks.0[0].checklist;
+
+        assert_eq!(checklist.len(), 2);
+        assert_eq!(checklist[0].children().len(), 2);
+        assert_eq!(
+            checklist[0].children()[1].children()[0]
+                .description(),
+            "Summarize"
+        );
+        assert_eq!(checklist.progress().to_string(), "2/5");
+        assert_eq!(
+            tasks.to_string(),
+            text.replace("\t\t", "        ") + "\n"
+        );
+    }
+
+    #[test]
+    fn takes_dates_from_the_clock() {
+        let text =
+            "1. [x] Buy milk [LOW]\n2. [-] Call mom [LOW]";
+        let tasks = parse(text).unwrap();
+
+        assert_eq!(tasks.0[0].creation_date, today());
+        assert_eq!(tasks.0[0].completed_at, Some(now()));
+        assert_eq!(tasks.0[1].cancelled_at, Some(now()));
+    }
+
+    #[test]
+    fn parses_notes() {
+        let text = "1. [ ] Review PR [HIGH] id:0000000000000001\n    * [ ] Run the tests\n    > See https://example.com/pr/42\n    >\n    >   - No new warnings\n\n2. [ ] Lunch [LOW] id:0000000000000002\n";
+
+        let tasks = parse(text).unwrap();
+
+        assert_eq!(
+            tasks.0[0].notes,
+            "See https://example.com/pr/42\n\n  - No new warnings"
+        );
+        assert_eq!(tasks.0[0].checklist.len(), 1);
+        assert!(tasks.0[1].notes.is_empty());
+        assert_eq!(tasks.to_string(), format!("{text}\n"));
+    }
+
+    #[test]
+    fn parses_statuses() {
+        let text = "1. [~] Write report [HIGH] id:0000000000000001\n2. [?] Hear back from Ana [LOW] id:0000000000000002\n";
+
+        let tasks = parse(text).unwrap();
+
+        assert_eq!(tasks.0[0].status, Status::InProgress);
+        assert_eq!(tasks.0[1].status, Status::Waiting);
+        assert_eq!(
+            tasks.to_string(),
+            text.replace('\n', "\n\n")
+        );
     }
 
     #[test]
@@ -280,20 +1048,35 @@ mod tests {
 
     #[test]
     fn parse_task_0() {
-        let task = "1. [ ] Fill out my tasks [HIGH]\n";
+        let task = "1. [ ] Fill out my tasks [HIGH] id:1\n";
 
         assert_eq!(
-            parse_task(task),
+            parse_task(task, now(), &schema()),
             Ok((
                 "",
                 Task {
                     idx: 1,
+                    id: id("1"),
                     name: "Fill out my tasks".into(),
-                    is_done: false,
+                    status: Status::Todo,
+                    completed_at: None,
+                    cancelled_at: None,
                     creation_date: today(),
+                    carried_over: 0,
                     due_date: None,
+                    due_time: None,
+                    wait_until: None,
+                    recurrence: None,
+                    depends_on: vec![],
                     priority: Priority::High,
-                    checklist: [].into_iter().collect()
+                    tags: BTreeSet::new(),
+                    project: None,
+                    context: None,
+                    checklist: [].into_iter().collect(),
+                    notes: String::new(),
+                    estimate: None,
+                    time_log: vec![],
+                    attributes: BTreeMap::new(),
                 }
             ))
         );
@@ -301,24 +1084,39 @@ mod tests {
 
     #[test]
     fn parse_task_1() {
-        let task = "1. [ ] Fill out my tasks [HIGH]\n  * [ ] Figure out how to use dodo\n";
+        let task = "1. [ ] Fill out my tasks [HIGH] id:1\n  * [ ] Figure out how to use dodo\n";
 
         assert_eq!(
-            parse_task(task),
+            parse_task(task, now(), &schema()),
             Ok((
                 "",
                 Task {
                     idx: 1,
+                    id: id("1"),
                     name: "Fill out my tasks".into(),
-                    is_done: false,
+                    status: Status::Todo,
+                    completed_at: None,
+                    cancelled_at: None,
                     creation_date: today(),
+                    carried_over: 0,
                     due_date: None,
+                    due_time: None,
+                    wait_until: None,
+                    recurrence: None,
+                    depends_on: vec![],
                     priority: Priority::High,
+                    tags: BTreeSet::new(),
+                    project: None,
+                    context: None,
                     checklist: [Checkbox::with_description(
                         "Figure out how to use dodo".into()
                     )]
                     .into_iter()
-                    .collect()
+                    .collect(),
+                    notes: String::new(),
+                    estimate: None,
+                    time_log: vec![],
+                    attributes: BTreeMap::new(),
                 }
             ))
         );
@@ -326,19 +1124,30 @@ mod tests {
 
     #[test]
     fn parse_task_2() {
-        let task = "1. [ ] Fill out my tasks [HIGH]\n  * [ ] Figure out how to use dodo\n* [x] Make this test pass\n";
+        let task = "1. [ ] Fill out my tasks [HIGH] id:1\n  * [ ] Figure out how to use dodo\n* [x] Make this test pass\n";
 
         assert_eq!(
-            parse_task(task),
+            parse_task(task, now(), &schema()),
             Ok((
                 "",
                 Task {
                     idx: 1,
+                    id: id("1"),
                     name: "Fill out my tasks".into(),
-                    is_done: false,
+                    status: Status::Todo,
+                    completed_at: None,
+                    cancelled_at: None,
                     creation_date: today(),
+                    carried_over: 0,
                     due_date: None,
+                    due_time: None,
+                    wait_until: None,
+                    recurrence: None,
+                    depends_on: vec![],
                     priority: Priority::High,
+                    tags: BTreeSet::new(),
+                    project: None,
+                    context: None,
                     checklist: [
                         Checkbox::with_description(
                             "Figure out how to use dodo".into()
@@ -349,7 +1158,11 @@ mod tests {
                         .with_status(true)
                     ]
                     .into_iter()
-                    .collect()
+                    .collect(),
+                    notes: String::new(),
+                    estimate: None,
+                    time_log: vec![],
+                    attributes: BTreeMap::new(),
                 }
             ))
         );
@@ -357,18 +1170,29 @@ mod tests {
 
     #[test]
     fn parses_many_tasks() {
-        let task = "1. [ ] Fill out my tasks [HIGH]\n  * [ ] Figure out how to use dodo\n* [x] Make this test pass\n2. [ ] Update taskset [HIGH]\n  * [ ] Do the dishes\n";
+        let task = "1. [ ] Fill out my tasks [HIGH] id:1\n  * [ ] Figure out how to use dodo\n* [x] Make this test pass\n2. [ ] Update taskset [HIGH] id:2\n  * [ ] Do the dishes\n";
 
         assert_eq!(
-            Parser::parse(task).unwrap(),
+            parse(task).unwrap(),
             TaskSet(vec![
                 Task {
                     idx: 1,
+                    id: id("1"),
                     name: "Fill out my tasks".into(),
-                    is_done: false,
+                    status: Status::Todo,
+                    completed_at: None,
+                    cancelled_at: None,
                     creation_date: today(),
+                    carried_over: 0,
                     due_date: None,
+                    due_time: None,
+                    wait_until: None,
+                    recurrence: None,
+                    depends_on: vec![],
                     priority: Priority::High,
+                    tags: BTreeSet::new(),
+                    project: None,
+                    context: None,
                     checklist: [
                         Checkbox::with_description(
                             "Figure out how to use dodo".into()
@@ -379,53 +1203,576 @@ mod tests {
                         .with_status(true)
                     ]
                     .into_iter()
-                    .collect()
+                    .collect(),
+                    notes: String::new(),
+                    estimate: None,
+                    time_log: vec![],
+                    attributes: BTreeMap::new(),
                 },
                 Task {
                     idx: 2,
+                    id: id("2"),
                     name: "Update taskset".into(),
-                    is_done: false,
+                    status: Status::Todo,
+                    completed_at: None,
+                    cancelled_at: None,
                     creation_date: today(),
+                    carried_over: 0,
                     due_date: None,
+                    due_time: None,
+                    wait_until: None,
+                    recurrence: None,
+                    depends_on: vec![],
                     priority: Priority::High,
+                    tags: BTreeSet::new(),
+                    project: None,
+                    context: None,
                     checklist: [Checkbox::with_description(
                         "Do the dishes".into()
                     )]
                     .into_iter()
-                    .collect()
+                    .collect(),
+                    notes: String::new(),
+                    estimate: None,
+                    time_log: vec![],
+                    attributes: BTreeMap::new(),
                 }
             ])
         );
     }
 
+    #[test]
+    fn tasks_without_ids_get_new_ones() {
+        let text =
+            "1. [ ] Call mom [LOW]\n2. [ ] Call dad [LOW]\n";
+
+        let tasks = parse(text).unwrap();
+
+        assert_ne!(tasks.0[0].id, tasks.0[1].id);
+    }
+
     #[test]
     fn parses_task_header() {
         assert_eq!(
-            parse_task_header("1. [ ] Fill out my tasks [HIGH]"),
+            parse_task_header(
+                "1. [ ] Fill out my tasks [HIGH]",
+                &schema()
+            ),
             Ok((
                 "",
                 TaskHeader {
                     idx: 1,
-                    is_checked: false,
-                    name: "Fill out my tasks",
-                    priority: Priority::High
+                    status: Status::Todo,
+                    name: "Fill out my tasks".into(),
+                    priority: Priority::High,
+                    attributes: vec![],
                 }
             ))
         );
 
         assert_eq!(
             parse_task_header(
-                "20.[x] Finish this test [MEDIUM]"
+                "20.[x] Finish this test [MEDIUM]",
+                &schema()
             ),
             Ok((
                 "",
                 TaskHeader {
                     idx: 20,
-                    is_checked: true,
-                    name: "Finish this test",
-                    priority: Priority::Medium
+                    status: Status::Done,
+                    name: "Finish this test".into(),
+                    priority: Priority::Medium,
+                    attributes: vec![],
+                }
+            ))
+        );
+
+        assert_eq!(
+            parse_task_header(
+                "3. [ ] Write report [LOW] due:2022-06-30 id:a1 +work #writing\n",
+                &schema()
+            ),
+            Ok((
+                "\n",
+                TaskHeader {
+                    idx: 3,
+                    status: Status::Todo,
+                    name: "Write report".into(),
+                    priority: Priority::Low,
+                    attributes: vec![
+                        Attribute::DueDate(
+                            NaiveDate::from_ymd(2022, 6, 30),
+                            None
+                        ),
+                        Attribute::Id(id("a1")),
+                        Attribute::Project("work"),
+                        Attribute::Tag("writing"),
+                    ],
                 }
             ))
         );
     }
+
+    #[test]
+    fn parses_custom_attributes() {
+        let text = "1. [ ] Fix login [HIGH] ticket:ENG-123 points:3 review:2023-01-09 id:1\n";
+
+        let tasks = parse(text).unwrap();
+        let attributes = &tasks.0[0].attributes;
+        assert_eq!(
+            attributes["points"],
+            AttributeValue::Number(3.0)
+        );
+        assert_eq!(
+            attributes["review"],
+            AttributeValue::Date(NaiveDate::from_ymd(
+                2023, 1, 9
+            ))
+        );
+        // Attributes are written in the order of their keys
+        assert!(tasks.to_string().contains(
+            "[HIGH] points:3 review:2023-01-09 ticket:ENG-123 id:"
+        ));
+
+        // Undeclared attributes are kept as strings
+        let text =
+            "1. [ ] Fix login [HIGH] customer:acme points:3\n";
+        let tasks = parse(text).unwrap();
+        assert_eq!(
+            tasks.0[0].attributes["customer"],
+            AttributeValue::String("acme".into())
+        );
+
+        for (text, hint) in [
+            (
+                "1. [ ] Fix login [HIGH] points:many\n",
+                "expected a number, such as '3' or '0.5'",
+            ),
+            (
+                "1. [ ] Fix login [HIGH] status:done\n",
+                "expected an attribute, such as 'due:2022-06-30', \
+                 or the end of the line",
+            ),
+        ] {
+            match parse(text) {
+                Err(Error::Parse(errors)) => {
+                    assert_eq!(errors[0].hint, hint)
+                }
+                other => panic!("expected an error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn parses_recurrences() {
+        let text = "1. [ ] Send invoices [HIGH] every:month:5 until:2026-12-31 id:1\n2. [ ] Standup notes [LOW] every:mon,wed id:2\n";
+
+        let tasks = parse(text).unwrap();
+
+        assert_eq!(
+            tasks.0[0].recurrence,
+            Some(Recurrence {
+                frequency: Frequency::Monthly(5),
+                until: Some(NaiveDate::from_ymd(2026, 12, 31)),
+            })
+        );
+        assert_eq!(
+            tasks.0[1].recurrence,
+            Some(Recurrence::new(Frequency::Weekly(vec![
+                Weekday::Mon,
+                Weekday::Wed
+            ])))
+        );
+
+        let text = "1. [ ] Standup notes [LOW] every:weekly\n";
+        assert!(parse(text).is_err());
+    }
+
+    #[test]
+    fn parses_snoozed_tasks() {
+        let text = "1. [ ] Renew passport [LOW] due:2026-12-01 wait:2026-11-01 id:1\n";
+
+        let tasks = parse(text).unwrap();
+        assert_eq!(
+            tasks.0[0].wait_until,
+            Some(NaiveDate::from_ymd(2026, 11, 1))
+        );
+        assert!(tasks
+            .to_string()
+            .contains(" due:2026-12-01 wait:2026-11-01 id:"));
+
+        let text = "1. [ ] Renew passport [LOW] wait:monday\n";
+        assert!(parse(text).is_err());
+    }
+
+    #[test]
+    fn parses_estimates() {
+        let text = "1. [ ] Write report [HIGH] est:1h30m id:1\n2. [ ] Buy milk [LOW] est:15m id:2\n";
+
+        let tasks = parse(text).unwrap();
+        assert_eq!(
+            tasks.0[0].estimate,
+            Some(Duration::minutes(90))
+        );
+        assert_eq!(
+            tasks.0[1].estimate,
+            Some(Duration::minutes(15))
+        );
+        assert!(tasks.to_string().contains(" est:1h30m id:"));
+
+        let text = "1. [ ] Write report [HIGH] est:soon\n";
+        assert!(parse(text).is_err());
+    }
+
+    #[test]
+    fn parses_dependencies() {
+        let text = "1. [ ] Write code [HIGH] id:0000000000000001\n\n// Blocked by 1. Write code\n2. [ ] Deploy [LOW] after:0000000000000001 id:0000000000000002\n\n";
+
+        let tasks = parse(text).unwrap();
+
+        assert_eq!(tasks.0[1].depends_on, [id("1")]);
+        assert_eq!(annotated(&tasks), text);
+    }
+
+    #[test]
+    fn marks_blocked_tasks() {
+        let mut tasks = parse(
+            "1. [ ] Deploy [LOW] after:2 id:1\n2. [ ] Write code [HIGH] id:2\n",
+        )
+        .unwrap();
+
+        let text = annotated(&tasks);
+        assert!(
+            text.starts_with("// Blocked by 2. Write code\n1. ")
+        );
+
+        tasks.0[1].status = Status::Done;
+        assert!(!annotated(&tasks).contains("Blocked"));
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(
+            parse_date("2022-06-30"),
+            Ok(("", NaiveDate::from_ymd(2022, 6, 30)))
+        );
+
+        assert!(parse_date("2022-13-30").is_err());
+        assert!(parse_date("tomorrow").is_err());
+    }
+
+    #[test]
+    fn round_trips_due_dates() {
+        let mut task = Task::new(
+            "Write report".into(),
+            Priority::Low,
+            today(),
+        );
+        task.idx = 1;
+        task.due_date = Some(NaiveDate::from_ymd(2022, 6, 30));
+        let mut tasks = TaskSet(vec![task.clone()]);
+
+        task.due_time = Some("15:00+02:00".parse().unwrap());
+        tasks.push(task);
+        assert!(tasks
+            .to_string()
+            .contains(" due:2022-06-30T15:00+02:00 "));
+
+        assert_eq!(parse(&tasks.to_string()).unwrap(), tasks);
+
+        let text =
+            "1. [ ] Write report [LOW] due:2022-06-30T25:00";
+        assert!(parse(text).is_err());
+    }
+
+    #[test]
+    fn reports_every_malformed_task() {
+        let text = "// A comment\n1. [ ] Fill out my tasks [URGENT]\n  * [ ] Figure out how to use dodo\n2. [ ] Update taskset [HIGH] id:2\n  * [y] Do the dishes\n3. [x] Water the plants [LOW]\n";
+
+        let errors = match parse(text) {
+            Err(Error::Parse(errors)) => errors,
+            other => {
+                panic!("expected parse errors, got {other:?}")
+            }
+        };
+
+        assert_eq!(
+            errors,
+            [
+                ParseError {
+                    line: 2,
+                    column: 26,
+                    text: "1. [ ] Fill out my tasks [URGENT]"
+                        .into(),
+                    hint:
+                        "expected a priority, such as '[HIGH]', \
+                           '[MEDIUM]' or '[LOW]'",
+                },
+                ParseError {
+                    line: 5,
+                    column: 5,
+                    text: "* [y] Do the dishes".into(),
+                    hint:
+                        "expected a checkmark, such as '[ ]' or \
+                           '[x]'",
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_comments() {
+        let text = "// A comment\n1. [ ] Fill out my tasks [HIGH]\n// Another one\n  * [ ] Figure out how to use dodo\n";
+
+        let tasks = parse(text).unwrap();
+
+        assert_eq!(tasks.0.len(), 1);
+        assert_eq!(tasks.0[0].checklist.len(), 1);
+    }
+
+    #[test]
+    fn parses_escaped_brackets() {
+        let text = "1. [ ] Fix \\[urgent\\] bug [HIGH] id:00000000000000f1\n    * [ ] Review PR \\[#42\\]\n\n";
+
+        let tasks = parse(text).unwrap();
+
+        assert_eq!(tasks.0[0].name, "Fix [urgent] bug");
+        assert_eq!(
+            tasks.0[0].checklist[0].description(),
+            "Review PR [#42]"
+        );
+        assert_eq!(tasks.to_string(), text);
+    }
+
+    fn text() -> impl Strategy<Value = String> {
+        // Brackets, backslashes and line breaks get picked more
+        // often than they otherwise would since they need
+        // escaping. Text can't start or end with whitespace, as
+        // it gets trimmed when parsed.
+        "([^\\s\\p{C}]((\\PC|[\\[\\]\\\\\n])*[^\\s\\p{C}])?)?"
+    }
+
+    fn word() -> impl Strategy<Value = String> {
+        "[^\\s\\[\\]]{1,12}"
+    }
+
+    fn date() -> impl Strategy<Value = NaiveDate> {
+        let epoch = NaiveDate::from_ymd(1970, 1, 1);
+
+        (0..50_000_i64)
+            .prop_map(move |days| epoch + Duration::days(days))
+    }
+
+    fn recurrence() -> impl Strategy<Value = Recurrence> {
+        let weekdays =
+            btree_set(0..7_usize, 1..7).prop_map(|days| {
+                let weekdays = days.into_iter().map(|day| {
+                    (0..day).fold(Weekday::Mon, |weekday, _| {
+                        weekday.succ()
+                    })
+                });
+                Frequency::Weekly(weekdays.collect())
+            });
+        let frequency = prop_oneof![
+            Just(Frequency::Daily),
+            weekdays,
+            (1..=31_u32).prop_map(Frequency::Monthly),
+            (1..1000_u32).prop_map(Frequency::EveryNDays),
+        ];
+
+        (frequency, option::of(date())).prop_map(
+            |(frequency, until)| Recurrence { frequency, until },
+        )
+    }
+
+    fn task_id() -> impl Strategy<Value = TaskId> {
+        any::<u64>()
+            .prop_map(|id| format!("{id:x}").parse().unwrap())
+    }
+
+    fn checkbox() -> impl Strategy<Value = Checkbox> {
+        let leaf = (
+            text(),
+            any::<bool>(),
+            option::of(priority()),
+            option::of(date()),
+            option::of(text()),
+        )
+            .prop_map(
+                |(description, is_done, priority, due, note)| {
+                    let mut checkbox =
+                        Checkbox::with_description(description)
+                            .with_status(is_done);
+                    if let Some(priority) = priority {
+                        checkbox =
+                            checkbox.with_priority(priority);
+                    }
+                    if let Some(due) = due {
+                        checkbox = checkbox.with_due_date(due);
+                    }
+                    if let Some(note) = note {
+                        checkbox = checkbox.with_note(note);
+                    }
+                    checkbox
+                },
+            );
+
+        leaf.prop_recursive(3, 12, 3, |inner| {
+            (inner.clone(), vec(inner, 0..3)).prop_map(
+                |(checkbox, children)| {
+                    checkbox.with_children(
+                        children.into_iter().collect(),
+                    )
+                },
+            )
+        })
+    }
+
+    fn due_time() -> impl Strategy<Value = DueTime> {
+        let timezone = (-(23 * 60 + 59)..=23 * 60 + 59_i32)
+            .prop_map(|minutes| {
+                Timezone::from(FixedOffset::east(minutes * 60))
+            });
+
+        (0..24_u32, 0..60_u32, option::of(timezone)).prop_map(
+            |(hours, minutes, timezone)| DueTime {
+                time: NaiveTime::from_hms(hours, minutes, 0),
+                timezone,
+            },
+        )
+    }
+
+    fn priority() -> impl Strategy<Value = Priority> {
+        prop_oneof![
+            Just(Priority::High),
+            Just(Priority::Medium),
+            Just(Priority::Low),
+        ]
+    }
+
+    fn attributes(
+    ) -> impl Strategy<Value = BTreeMap<String, AttributeValue>>
+    {
+        let number = any::<f64>()
+            .prop_filter("numbers are finite", |n| {
+                n.is_finite()
+            });
+        let declared = (
+            option::of(word().prop_map(AttributeValue::String)),
+            option::of(number.prop_map(AttributeValue::Number)),
+            option::of(date().prop_map(AttributeValue::Date)),
+            option::of((0..100_000_i64).prop_map(|minutes| {
+                AttributeValue::Duration(Duration::minutes(
+                    minutes,
+                ))
+            })),
+        )
+            .prop_map(
+                |(ticket, points, review, spent)| {
+                    [
+                        ("ticket", ticket),
+                        ("points", points),
+                        ("review", review),
+                        ("spent", spent),
+                    ]
+                    .into_iter()
+                    .filter_map(|(key, value)| {
+                        Some((key.to_owned(), value?))
+                    })
+                    .collect::<Vec<_>>()
+                },
+            );
+        // The values of undeclared attributes are read as strings
+        let key = "[a-zA-Z0-9_-]{1,8}".prop_filter(
+            "keys are neither reserved nor declared",
+            |key: &String| {
+                !AttributeSchema::RESERVED
+                    .contains(&key.as_str())
+                    && schema().kind(key).is_none()
+            },
+        );
+        let undeclared = btree_map(
+            key,
+            word().prop_map(AttributeValue::String),
+            0..3,
+        );
+
+        (declared, undeclared).prop_map(
+            |(declared, mut attributes)| {
+                attributes.extend(declared);
+                attributes
+            },
+        )
+    }
+
+    fn task() -> impl Strategy<Value = Task> {
+        let checkboxes = vec(checkbox(), 0..4);
+
+        (
+            text(),
+            select(Status::ALL.to_vec()),
+            priority(),
+            (
+                option::of((date(), option::of(due_time()))),
+                option::of(date()),
+            ),
+            option::of(recurrence()),
+            vec(task_id(), 0..3),
+            btree_set(word(), 0..3),
+            option::of(word()),
+            option::of(word()),
+            checkboxes,
+            (
+                vec("\\PC*", 0..3),
+                option::of(0..100_000_i64),
+                attributes(),
+            ),
+        )
+            .prop_map(
+                |(
+                    name,
+                    status,
+                    priority,
+                    (due, wait_until),
+                    recurrence,
+                    depends_on,
+                    tags,
+                    project,
+                    context,
+                    checkboxes,
+                    (notes, estimate, attributes),
+                )| {
+                    // Tasks the parser reads are created now
+                    let mut task =
+                        Task::new(name, priority, today());
+                    task.set_status(status, now());
+                    task.due_date = due.map(|(date, _)| date);
+                    task.due_time =
+                        due.and_then(|(_, time)| time);
+                    task.wait_until = wait_until;
+                    task.recurrence = recurrence;
+                    task.depends_on = depends_on;
+                    task.tags = tags;
+                    task.project = project;
+                    task.context = context;
+                    task.checklist =
+                        checkboxes.into_iter().collect();
+                    task.notes = notes.join("\n");
+                    task.estimate =
+                        estimate.map(Duration::minutes);
+                    task.attributes = attributes;
+                    task
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn display_and_parse_round_trip(tasks in vec(task(), 0..8)) {
+            let mut tasks = TaskSet(tasks);
+            tasks.renumber();
+
+            prop_assert_eq!(parse(&tasks.to_string()).unwrap(), tasks);
+        }
+    }
 }
@@ -0,0 +1,350 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{
+    format_duration, is_word_char, parse_duration,
+};
+
+/// The type of the values of a custom attribute.
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeKind {
+    /// Any text without whitespace or brackets, such as
+    /// "ENG-123"
+    String,
+    /// A number, such as "3" or "0.5"
+    Number,
+    /// A date as YYYY-MM-DD, such as "2023-01-02"
+    Date,
+    /// A duration in hours and minutes, such as "1h30m"
+    Duration,
+}
+
+impl AttributeKind {
+    /// Parses `input` as a value of this type.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{AttributeKind, AttributeValue};
+    /// assert_eq!(AttributeKind::Number.parse("3"), Some(AttributeValue::Number(3.0)));
+    /// assert_eq!(AttributeKind::Number.parse("3pts"), None);
+    /// ```
+    pub fn parse(self, input: &str) -> Option<AttributeValue> {
+        if input.is_empty() || !input.chars().all(is_word_char) {
+            return None;
+        }
+
+        match self {
+            AttributeKind::String => {
+                Some(AttributeValue::String(input.to_owned()))
+            }
+            AttributeKind::Number => input
+                .parse()
+                .ok()
+                .filter(|number: &f64| number.is_finite())
+                .map(AttributeValue::Number),
+            AttributeKind::Date => {
+                NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                    .ok()
+                    .map(AttributeValue::Date)
+            }
+            AttributeKind::Duration => parse_duration(input)
+                .map(AttributeValue::Duration),
+        }
+    }
+}
+
+impl Display for AttributeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AttributeKind::String => "string",
+            AttributeKind::Number => "number",
+            AttributeKind::Date => "date",
+            AttributeKind::Duration => "duration",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// The value of a custom attribute of a task, written after its
+/// key as in "ticket:ENG-123".
+///
+/// Values of the same type are ordered naturally, and values of
+/// different types by their type.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum AttributeValue {
+    String(String),
+    Number(f64),
+    Date(NaiveDate),
+    Duration(
+        #[serde(with = "crate::time_log::seconds")] Duration,
+    ),
+}
+
+impl AttributeValue {
+    /// The type of this value.
+    pub fn kind(&self) -> AttributeKind {
+        match self {
+            AttributeValue::String(_) => AttributeKind::String,
+            AttributeValue::Number(_) => AttributeKind::Number,
+            AttributeValue::Date(_) => AttributeKind::Date,
+            AttributeValue::Duration(_) => {
+                AttributeKind::Duration
+            }
+        }
+    }
+}
+
+impl Display for AttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeValue::String(text) => write!(f, "{text}"),
+            AttributeValue::Number(number) => {
+                write!(f, "{number}")
+            }
+            AttributeValue::Date(date) => write!(f, "{date}"),
+            AttributeValue::Duration(duration) => {
+                write!(f, "{}", format_duration(*duration))
+            }
+        }
+    }
+}
+
+impl Ord for AttributeValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use AttributeValue::*;
+
+        match (self, other) {
+            (String(a), String(b)) => a.cmp(b),
+            // Numbers are always finite, but a total order keeps
+            // this well-defined regardless
+            (Number(a), Number(b)) => a.total_cmp(b),
+            (Date(a), Date(b)) => a.cmp(b),
+            (Duration(a), Duration(b)) => a.cmp(b),
+            (a, b) => (a.kind() as u8).cmp(&(b.kind() as u8)),
+        }
+    }
+}
+
+impl PartialOrd for AttributeValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for AttributeValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for AttributeValue {}
+
+/// The custom attributes that tasks may have, along with the
+/// type of their values.
+///
+/// Keys are made out of letters, digits, dashes and
+/// underscores, and can't be any of the [reserved
+/// ones](AttributeSchema::RESERVED).
+///
+/// ```rust
+/// # use dodo_internals::{AttributeKind, AttributeSchema, AttributeValue};
+/// let mut schema = AttributeSchema::default();
+/// schema.declare("points", AttributeKind::Number).unwrap();
+///
+/// assert_eq!(schema.parse("points", "3"), Ok(AttributeValue::Number(3.0)));
+/// assert!(schema.parse("points", "many").is_err());
+/// assert!(schema.parse("ticket", "ENG-123").is_err());
+/// assert!(schema.declare("due", AttributeKind::Date).is_err());
+/// ```
+#[derive(
+    Debug, Serialize, Deserialize, Default, PartialEq, Eq, Clone,
+)]
+#[serde(
+    try_from = "BTreeMap<String, AttributeKind>",
+    into = "BTreeMap<String, AttributeKind>"
+)]
+pub struct AttributeSchema(BTreeMap<String, AttributeKind>);
+
+impl AttributeSchema {
+    /// The keys that are built into dodo, be it in the text of
+    /// tasks or in [queries](crate::Query), which custom
+    /// attributes can't use.
+    pub const RESERVED: &'static [&'static str] = &[
+        "due", "wait", "every", "until", "est", "after", "id",
+        "tag", "priority", "status", "name",
+    ];
+
+    /// Adds an attribute to this schema, replacing the type of
+    /// any attribute with the same key.
+    pub fn declare(
+        &mut self,
+        key: &str,
+        kind: AttributeKind,
+    ) -> Result<(), String> {
+        let is_key_char = |ch: char| {
+            ch.is_ascii_alphanumeric() || ch == '-' || ch == '_'
+        };
+        if key.is_empty() || !key.chars().all(is_key_char) {
+            return Err(format!(
+                "invalid attribute '{key}', expected a name made \
+                 out of letters, digits, dashes and underscores"
+            ));
+        }
+        if Self::RESERVED.contains(&key) {
+            return Err(format!(
+                "'{key}' is a built-in attribute and can't be \
+                 redeclared"
+            ));
+        }
+
+        self.0.insert(key.to_owned(), kind);
+
+        Ok(())
+    }
+
+    /// The type of the attribute with the given key, if it was
+    /// declared.
+    pub fn kind(&self, key: &str) -> Option<AttributeKind> {
+        self.0.get(key).copied()
+    }
+
+    /// Parses `value` as a value of the attribute with the given
+    /// key.
+    pub fn parse(
+        &self,
+        key: &str,
+        value: &str,
+    ) -> Result<AttributeValue, String> {
+        let kind = self.kind(key).ok_or_else(|| {
+            format!("there's no attribute named '{key}'")
+        })?;
+
+        kind.parse(value).ok_or_else(|| {
+            format!(
+                "invalid value '{value}' for '{key}', expected a \
+                 {kind}"
+            )
+        })
+    }
+}
+
+impl TryFrom<BTreeMap<String, AttributeKind>>
+    for AttributeSchema
+{
+    type Error = String;
+
+    fn try_from(
+        attributes: BTreeMap<String, AttributeKind>,
+    ) -> Result<Self, Self::Error> {
+        let mut schema = Self::default();
+        for (key, kind) in attributes {
+            schema.declare(&key, kind)?;
+        }
+
+        Ok(schema)
+    }
+}
+
+impl From<AttributeSchema> for BTreeMap<String, AttributeKind> {
+    fn from(schema: AttributeSchema) -> Self {
+        schema.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+
+    use super::{
+        AttributeKind, AttributeSchema, AttributeValue,
+    };
+
+    #[test]
+    fn parses_values_of_each_type() {
+        use AttributeKind::*;
+
+        assert_eq!(
+            String.parse("ENG-123"),
+            Some(AttributeValue::String("ENG-123".into()))
+        );
+        assert_eq!(
+            Number.parse("-0.5").unwrap().to_string(),
+            "-0.5"
+        );
+        assert_eq!(Number.parse("3").unwrap().to_string(), "3");
+        assert_eq!(
+            Date.parse("2023-01-02"),
+            Some(AttributeValue::Date(NaiveDate::from_ymd(
+                2023, 1, 2
+            )))
+        );
+        assert_eq!(
+            Duration.parse("1h30m"),
+            Some(AttributeValue::Duration(
+                chrono::Duration::minutes(90)
+            ))
+        );
+
+        for (kind, invalid) in [
+            (String, ""),
+            (String, "two words"),
+            (String, "[x]"),
+            (Number, "NaN"),
+            (Number, "inf"),
+            (Date, "2023-02-30"),
+            (Duration, "soon"),
+        ] {
+            assert_eq!(kind.parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn orders_values() {
+        let mut values = [
+            AttributeValue::Number(10.0),
+            AttributeValue::Number(-2.0),
+            AttributeValue::Duration(Duration::hours(1)),
+            AttributeValue::String("acme".into()),
+            AttributeValue::Number(3.0),
+        ];
+        values.sort();
+
+        let texts: Vec<_> =
+            values.iter().map(ToString::to_string).collect();
+        assert_eq!(texts, ["acme", "-2", "3", "10", "1h"]);
+    }
+
+    #[test]
+    fn validates_keys() {
+        let mut schema = AttributeSchema::default();
+
+        assert!(schema
+            .declare("customer_id", AttributeKind::String)
+            .is_ok());
+        assert!(schema
+            .declare("", AttributeKind::String)
+            .is_err());
+        assert!(schema
+            .declare("two words", AttributeKind::String)
+            .is_err());
+        assert!(schema
+            .declare("est", AttributeKind::Number)
+            .is_err());
+        assert!(schema
+            .declare("status", AttributeKind::String)
+            .is_err());
+        assert_eq!(
+            schema.kind("customer_id"),
+            Some(AttributeKind::String)
+        );
+    }
+}
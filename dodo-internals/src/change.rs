@@ -0,0 +1,381 @@
+use std::fmt::{self, Display};
+
+use chrono::NaiveDate;
+
+use crate::{
+    utils::format_duration, Priority, Status, Task, TaskSet,
+};
+
+/// A single difference between two versions of a
+/// [`TaskSet`](crate::TaskSet).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Change<'a> {
+    /// A task that only exists in the newer task set
+    Added(&'a Task),
+    /// A task that only exists in the older task set
+    Removed(&'a Task),
+    /// A task that was marked as done
+    Completed(&'a Task),
+    /// A task that was cancelled
+    Cancelled(&'a Task),
+    /// A done or cancelled task that was opened again
+    Reopened(&'a Task),
+    /// A task that went from one open status to another, such
+    /// as from in progress to blocked
+    StatusChanged { from: Status, task: &'a Task },
+    /// A task whose name was changed
+    Renamed { from: &'a str, task: &'a Task },
+    /// A task whose priority was changed
+    Reprioritized { from: Priority, task: &'a Task },
+    /// A task whose due date was changed
+    Rescheduled {
+        from: Option<NaiveDate>,
+        task: &'a Task,
+    },
+    /// A task that started or stopped recurring, or whose
+    /// recurrence was changed
+    Recurred(&'a Task),
+    /// A task whose dependencies were changed
+    Redepended(&'a Task),
+    /// A task whose tags, project or context were changed
+    Recategorized(&'a Task),
+    /// A task whose checklist was changed
+    ChecklistEdited(&'a Task),
+    /// A task whose notes were changed
+    NotesEdited(&'a Task),
+    /// A task whose time estimate was changed
+    Reestimated(&'a Task),
+    /// A task that was snoozed, or woken up
+    Snoozed(&'a Task),
+    /// A task whose custom attributes were changed
+    Reattributed(&'a Task),
+}
+
+impl Display for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added(task) => {
+                write!(f, "+ Added '{}'", task.name)
+            }
+            Change::Removed(task) => {
+                write!(f, "- Removed '{}'", task.name)
+            }
+            Change::Completed(task) => {
+                write!(f, "✓ Completed '{}'", task.name)
+            }
+            Change::Cancelled(task) => {
+                write!(f, "✗ Cancelled '{}'", task.name)
+            }
+            Change::Reopened(task) => {
+                write!(f, "↺ Reopened '{}'", task.name)
+            }
+            Change::StatusChanged { from, task } => write!(
+                f,
+                "~ Changed status of '{}' from {from} to {}",
+                task.name, task.status
+            ),
+            Change::Renamed { from, task } => {
+                write!(
+                    f,
+                    "~ Renamed '{from}' to '{}'",
+                    task.name
+                )
+            }
+            Change::Reprioritized { from, task } => write!(
+                f,
+                "~ Changed priority of '{}' from {from} to {}",
+                task.name, task.priority
+            ),
+            Change::Rescheduled { from: _, task } => {
+                let name = &task.name;
+                match (task.due_date, task.due_time) {
+                    (Some(date), Some(time)) => write!(
+                        f,
+                        "~ Changed due date of '{name}' to {date} \
+                         at {time}"
+                    ),
+                    (Some(to), None) => write!(
+                        f,
+                        "~ Changed due date of '{name}' to {to}"
+                    ),
+                    (None, _) => write!(
+                        f,
+                        "~ Removed due date of '{name}'"
+                    ),
+                }
+            }
+            Change::Recurred(task) => match &task.recurrence {
+                Some(recurrence) => write!(
+                    f,
+                    "~ Changed recurrence of '{}' to {recurrence}",
+                    task.name
+                ),
+                None => write!(
+                    f,
+                    "~ '{}' no longer recurs",
+                    task.name
+                ),
+            },
+            Change::Redepended(task) => write!(
+                f,
+                "~ Changed what '{}' depends on",
+                task.name
+            ),
+            Change::Recategorized(task) => write!(
+                f,
+                "~ Changed tags, project or context of '{}'",
+                task.name
+            ),
+            Change::ChecklistEdited(task) => {
+                write!(
+                    f,
+                    "~ Edited checklist of '{}'",
+                    task.name
+                )
+            }
+            Change::NotesEdited(task) => {
+                write!(f, "~ Edited notes of '{}'", task.name)
+            }
+            Change::Snoozed(task) => match task.wait_until {
+                Some(date) => write!(
+                    f,
+                    "~ Snoozed '{}' until {date}",
+                    task.name
+                ),
+                None => write!(
+                    f,
+                    "~ '{}' is no longer snoozed",
+                    task.name
+                ),
+            },
+            Change::Reestimated(task) => match task.estimate {
+                Some(estimate) => write!(
+                    f,
+                    "~ Changed estimate of '{}' to {}",
+                    task.name,
+                    format_duration(estimate)
+                ),
+                None => write!(
+                    f,
+                    "~ Removed estimate of '{}'",
+                    task.name
+                ),
+            },
+            Change::Reattributed(task) => write!(
+                f,
+                "~ Changed attributes of '{}'",
+                task.name
+            ),
+        }
+    }
+}
+
+impl TaskSet {
+    /// Lists what changed between this task set and `newer`.
+    ///
+    /// Tasks are matched by their ID, their name or, failing
+    /// that, by their index.
+    pub fn diff<'a>(
+        &'a self,
+        newer: &'a TaskSet,
+    ) -> Vec<Change<'a>> {
+        let mut changes = Vec::new();
+
+        let matches = self.correspondences(newer);
+
+        for (pos, old) in self.0.iter().enumerate() {
+            let new = match matches
+                .iter()
+                .position(|m| *m == Some(pos))
+            {
+                Some(new_pos) => &newer.0[new_pos],
+                None => {
+                    changes.push(Change::Removed(old));
+                    continue;
+                }
+            };
+
+            if old.name != new.name {
+                changes.push(Change::Renamed {
+                    from: &old.name,
+                    task: new,
+                });
+            }
+
+            if old.status != new.status {
+                changes.push(match new.status {
+                    Status::Done => Change::Completed(new),
+                    Status::Cancelled => Change::Cancelled(new),
+                    _ if old.status.is_closed() => {
+                        Change::Reopened(new)
+                    }
+                    _ => Change::StatusChanged {
+                        from: old.status,
+                        task: new,
+                    },
+                });
+            }
+
+            if old.priority != new.priority {
+                changes.push(Change::Reprioritized {
+                    from: old.priority,
+                    task: new,
+                });
+            }
+
+            if old.due_date != new.due_date
+                || old.due_time != new.due_time
+            {
+                changes.push(Change::Rescheduled {
+                    from: old.due_date,
+                    task: new,
+                });
+            }
+
+            // Completing a recurring task hands its recurrence
+            // over to its next instance, which shows up as added
+            let was_handed_over = new.recurrence.is_none()
+                && new.is_done()
+                && !old.is_done();
+            if old.recurrence != new.recurrence
+                && !was_handed_over
+            {
+                changes.push(Change::Recurred(new));
+            }
+
+            if old.depends_on != new.depends_on {
+                changes.push(Change::Redepended(new));
+            }
+
+            if old.tags != new.tags
+                || old.project != new.project
+                || old.context != new.context
+            {
+                changes.push(Change::Recategorized(new));
+            }
+
+            if old.checklist != new.checklist {
+                changes.push(Change::ChecklistEdited(new));
+            }
+
+            if old.notes != new.notes {
+                changes.push(Change::NotesEdited(new));
+            }
+
+            if old.wait_until != new.wait_until {
+                changes.push(Change::Snoozed(new));
+            }
+
+            if old.estimate != new.estimate {
+                changes.push(Change::Reestimated(new));
+            }
+
+            if old.attributes != new.attributes {
+                changes.push(Change::Reattributed(new));
+            }
+        }
+
+        let added = newer
+            .0
+            .iter()
+            .zip(matches)
+            .filter(|(_, matched)| matched.is_none())
+            .map(|(new, _)| Change::Added(new));
+
+        changes.extend(added);
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::Change;
+    use crate::{
+        test_utils::today, Priority, Status, Task, TaskId,
+        TaskSet,
+    };
+
+    fn task(idx: usize, name: &str) -> Task {
+        Task {
+            idx,
+            id: TaskId::generate(),
+            name: name.into(),
+            status: Status::Todo,
+            completed_at: None,
+            cancelled_at: None,
+            creation_date: today(),
+            carried_over: 0,
+            due_date: None,
+            due_time: None,
+            wait_until: None,
+            recurrence: None,
+            depends_on: vec![],
+            priority: Priority::Low,
+            tags: BTreeSet::new(),
+            project: None,
+            context: None,
+            checklist: [].into_iter().collect(),
+            notes: String::new(),
+            estimate: None,
+            time_log: vec![],
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn no_changes() {
+        let set = TaskSet(vec![task(1, "A"), task(2, "B")]);
+
+        assert!(set.diff(&set.clone()).is_empty());
+    }
+
+    #[test]
+    fn detects_changes() {
+        let old = TaskSet(vec![task(1, "A"), task(2, "B")]);
+
+        let mut done = old.0[0].clone();
+        done.status = Status::Done;
+        done.priority = Priority::High;
+        let new = TaskSet(vec![done.clone(), task(3, "C")]);
+
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                Change::Completed(&done),
+                Change::Reprioritized {
+                    from: Priority::Low,
+                    task: &done
+                },
+                Change::Removed(&old.0[1]),
+                Change::Added(&new.0[1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_status_changes() {
+        let old = TaskSet(vec![task(1, "A"), task(2, "B")]);
+
+        let mut new = old.clone();
+        new.0[0].status = Status::Blocked;
+        new.0[1].status = Status::Cancelled;
+
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                Change::StatusChanged {
+                    from: Status::Todo,
+                    task: &new.0[0]
+                },
+                Change::Cancelled(&new.0[1]),
+            ]
+        );
+        assert_eq!(
+            new.diff(&old)[1],
+            Change::Reopened(&old.0[1])
+        );
+    }
+}
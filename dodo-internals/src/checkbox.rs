@@ -1,23 +1,45 @@
 use std::{fmt::Display, ops::Deref};
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    checklist::Progress, utils::escape, Checklist, Priority,
+};
+
 /// A checkbox that belogns to a checklist.
-/// Can be turned on or off and has a description.
+/// Can be turned on or off, has a description and may have checkboxes of its own.
+/// It may also have a priority, a due date and a short note.
 #[non_exhaustive]
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
-// TODO: allow a specific checkbox to have a due-date?
 pub struct Checkbox {
     description: String,
     is_done: bool,
+    priority: Option<Priority>,
+    due_date: Option<NaiveDate>,
+    note: Option<String>,
+    children: Checklist,
 }
 
+/// Only displays the checkbox itself, not its children.
+/// The priority, due date and note each follow the description between brackets.
 impl Display for Checkbox {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let is_done = if self.is_done { "x" } else { " " };
-        let desc = &self.description;
+        let desc = escape(&self.description);
+
+        write!(f, "[{is_done}] {desc}")?;
+        if let Some(priority) = self.priority {
+            write!(f, " [{priority}]")?;
+        }
+        if let Some(due_date) = self.due_date {
+            write!(f, " [due:{due_date}]")?;
+        }
+        if let Some(note) = &self.note {
+            write!(f, " [note: {}]", escape(note))?;
+        }
 
-        write!(f, "[{is_done}] {desc}")
+        Ok(())
     }
 }
 
@@ -27,6 +49,10 @@ impl Checkbox {
         Self {
             description: name,
             is_done: false,
+            priority: None,
+            due_date: None,
+            note: None,
+            children: Checklist::with_checkboxes(vec![]),
         }
     }
 
@@ -34,6 +60,55 @@ impl Checkbox {
         Self { is_done, ..self }
     }
 
+    pub fn with_priority(self, priority: Priority) -> Self {
+        Self {
+            priority: Some(priority),
+            ..self
+        }
+    }
+
+    pub fn with_due_date(self, due_date: NaiveDate) -> Self {
+        Self {
+            due_date: Some(due_date),
+            ..self
+        }
+    }
+
+    pub fn with_note(self, note: String) -> Self {
+        Self {
+            note: Some(note),
+            ..self
+        }
+    }
+
+    /// Replaces the checkboxes nested under this one.
+    pub fn with_children(self, children: Checklist) -> Self {
+        Self { children, ..self }
+    }
+
+    /// The checkboxes nested under this one
+    pub fn children(&self) -> &Checklist {
+        &self.children
+    }
+
+    /// The checkboxes nested under this one
+    pub fn children_mut(&mut self) -> &mut Checklist {
+        &mut self.children
+    }
+
+    /// How many of the checkboxes nested under this one, at any depth, are done.
+    /// ```rust
+    /// use dodo_internals::Checkbox;
+    /// let sources = Checkbox::with_description("Find sources".into()).with_status(true);
+    /// let notes = Checkbox::with_description("Take notes".into());
+    /// let research = Checkbox::with_description("Research".into())
+    ///     .with_children(vec![sources, notes].into_iter().collect());
+    /// assert_eq!(research.progress().to_string(), "1/2");
+    /// ```
+    pub fn progress(&self) -> Progress {
+        self.children.progress()
+    }
+
     /// The description of this checkbox
     /// ```rust
     /// use dodo_internals::Checkbox;
@@ -44,6 +119,36 @@ impl Checkbox {
         &self.description
     }
 
+    /// This checkbox's priority, if it has one
+    pub fn priority(&self) -> Option<Priority> {
+        self.priority
+    }
+
+    /// This checkbox's due date, if it has one
+    pub fn due_date(&self) -> Option<NaiveDate> {
+        self.due_date
+    }
+
+    /// The note attached to this checkbox, if there's one
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// Returns true if this checkbox isn't done and its due date is before `today`.
+    /// ```rust
+    /// use dodo_internals::{chrono::NaiveDate, Checkbox};
+    /// let due = NaiveDate::from_ymd(2023, 1, 1);
+    /// let mut item = Checkbox::with_description("Book flights".into()).with_due_date(due);
+    /// assert!(!item.is_overdue(due));
+    /// assert!(item.is_overdue(due.succ()));
+    /// item.toggle();
+    /// assert!(!item.is_overdue(due.succ()));
+    /// ```
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.is_done
+            && self.due_date.is_some_and(|due| due < today)
+    }
+
     /// Toggles a checkbox on or off (in other words, done or not done).
     /// ```rust
     /// use dodo_internals::Checkbox;
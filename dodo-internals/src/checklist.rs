@@ -4,6 +4,7 @@ use std::{
     ops::{Deref, Index},
 };
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 use crate::Checkbox;
@@ -15,13 +16,27 @@ pub struct Checklist {
     checkboxes: Vec<Checkbox>,
 }
 
-impl Display for Checklist {
+/// How many checkboxes are done out of how many there are.
+/// Displays as "done/total", such as "3/7".
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl Display for Progress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for checkbox in &self.checkboxes {
-            writeln!(f, "    * {}", checkbox)?;
-        }
+        write!(f, "{}/{}", self.done, self.total)
+    }
+}
 
-        Ok(())
+/// Each checkbox goes in its own line, indented by four spaces for each level of nesting.
+impl Display for Checklist {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        self.write_indented(f, 1)
     }
 }
 
@@ -53,6 +68,83 @@ impl Checklist {
         self.checkboxes.len()
     }
 
+    fn write_indented(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+    ) -> std::fmt::Result {
+        for checkbox in &self.checkboxes {
+            writeln!(
+                f,
+                "{:indent$}* {}",
+                "",
+                checkbox,
+                indent = 4 * depth
+            )?;
+            checkbox.children().write_indented(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Counts the checkboxes in this checklist, at any depth, and how many of them are done.
+    /// ```rust
+    /// use dodo_internals::{Checkbox, Checklist};
+    /// let done = Checkbox::with_description("Done".into()).with_status(true);
+    /// let pending = Checkbox::with_description("Pending".into());
+    /// let parent = pending.clone().with_children(vec![done.clone(), pending].into_iter().collect());
+    /// let checklist: Checklist = vec![done, parent].into_iter().collect();
+    /// assert_eq!(checklist.progress().to_string(), "2/4");
+    /// ```
+    pub fn progress(&self) -> Progress {
+        self.checkboxes.iter().fold(
+            Progress::default(),
+            |progress, checkbox| {
+                let nested = checkbox.progress();
+
+                Progress {
+                    done: progress.done
+                        + nested.done
+                        + usize::from(checkbox.is_done()),
+                    total: progress.total + nested.total + 1,
+                }
+            },
+        )
+    }
+
+    /// Unchecks every checkbox in this checklist, at any depth.
+    pub fn reset(&mut self) {
+        for checkbox in &mut self.checkboxes {
+            if checkbox.is_done() {
+                checkbox.toggle();
+            }
+            checkbox.children_mut().reset();
+        }
+    }
+
+    /// Returns the checkboxes in this checklist, at any depth, that are past their due date.
+    /// ```rust
+    /// use dodo_internals::{chrono::NaiveDate, Checkbox, Checklist};
+    /// let due = NaiveDate::from_ymd(2023, 1, 1);
+    /// let flights = Checkbox::with_description("Book flights".into()).with_due_date(due);
+    /// let trip = Checkbox::with_description("Plan the trip".into())
+    ///     .with_children(vec![flights].into_iter().collect());
+    /// let checklist: Checklist = vec![trip].into_iter().collect();
+    /// let overdue: Vec<_> = checklist.overdue(due.succ()).iter().map(|checkbox| checkbox.description()).collect();
+    /// assert_eq!(overdue, ["Book flights"]);
+    /// ```
+    pub fn overdue(&self, today: NaiveDate) -> Vec<&Checkbox> {
+        let mut overdue = Vec::new();
+        for checkbox in &self.checkboxes {
+            if checkbox.is_overdue(today) {
+                overdue.push(checkbox);
+            }
+            overdue.extend(checkbox.children().overdue(today));
+        }
+
+        overdue
+    }
+
     /// Returns true if the all of the checkboxes in this checklist are done.
     /// ```rust
     /// use dodo_internals::{Checkbox, Checklist};
@@ -103,11 +195,63 @@ impl Checklist {
         self.checkboxes.push(elem);
     }
 
-    /// Removes a checkbox from the list and returns it.
-    /// Does not preserve ordering.
+    /// Removes a checkbox from the list and returns it, shifting the ones after it up.
     /// Panics if `index` is out of bounds.
+    /// ```rust
+    /// # use dodo_internals::{Checkbox, Checklist};
+    /// let mut checklist: Checklist = ["Wash", "Dry", "Fold"].into_iter().map(|name| Checkbox::with_description(name.into())).collect();
+    /// assert_eq!(checklist.remove(0).description(), "Wash");
+    /// assert_eq!(checklist[0].description(), "Dry");
+    /// assert_eq!(checklist[1].description(), "Fold");
+    /// ```
     pub fn remove(&mut self, index: usize) -> Checkbox {
-        self.checkboxes.swap_remove(index)
+        self.checkboxes.remove(index)
+    }
+
+    /// Inserts a checkbox at `index`, shifting the ones after it down.
+    /// Panics if `index` is greater than the length of the checklist.
+    pub fn insert(&mut self, index: usize, checkbox: Checkbox) {
+        self.checkboxes.insert(index, checkbox);
+    }
+
+    /// Moves the checkbox at `from` so that it ends up at `to`, keeping the order of the rest.
+    /// Panics if either index is out of bounds.
+    /// ```rust
+    /// # use dodo_internals::{Checkbox, Checklist};
+    /// let mut checklist: Checklist = ["Wash", "Dry", "Fold"].into_iter().map(|name| Checkbox::with_description(name.into())).collect();
+    /// checklist.move_item(2, 0);
+    /// let order: Vec<_> = checklist.iter().map(Checkbox::description).collect();
+    /// assert_eq!(order, ["Fold", "Wash", "Dry"]);
+    /// ```
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        assert!(
+            to < self.checkboxes.len(),
+            "cannot move a checkbox to index {to} of a checklist \
+             of length {}",
+            self.checkboxes.len()
+        );
+
+        let checkbox = self.checkboxes.remove(from);
+        self.checkboxes.insert(to, checkbox);
+    }
+
+    /// Moves the checkboxes that aren't done before the ones that are, at any depth.
+    /// Checkboxes keep their order otherwise.
+    pub fn sort_by_status(&mut self) {
+        self.checkboxes.sort_by_key(Checkbox::is_done);
+
+        for checkbox in &mut self.checkboxes {
+            checkbox.children_mut().sort_by_status();
+        }
+    }
+
+    /// Keeps only the checkboxes for which `keep` returns true, in the same order.
+    /// Checkboxes that get removed take the ones nested under them along.
+    pub fn retain(
+        &mut self,
+        keep: impl FnMut(&Checkbox) -> bool,
+    ) {
+        self.checkboxes.retain(keep);
     }
 }
 
@@ -186,4 +330,74 @@ mod tests {
         assert!(checkboxes.get_mut(9).is_some());
         assert!(checkboxes.get_mut(10).is_none());
     }
+
+    #[test]
+    fn nested_checkboxes() {
+        let leaf = |name: &str| {
+            Checkbox::with_description(name.into())
+                .with_status(true)
+        };
+        let outline =
+            Checkbox::with_description("Outline".into())
+                .with_children(
+                    vec![leaf("Intro"), leaf("Body")]
+                        .into_iter()
+                        .collect(),
+                );
+        let research =
+            Checkbox::with_description("Research".into())
+                .with_children(
+                    vec![leaf("Sources"), outline]
+                        .into_iter()
+                        .collect(),
+                );
+
+        let mut checklist: Checklist =
+            vec![research].into_iter().collect();
+        assert_eq!(
+            checklist.to_string(),
+            "    * [ ] Research\n        * [x] Sources\n        * [ ] Outline\n            * [x] Intro\n            * [x] Body\n"
+        );
+        assert_eq!(checklist.progress().to_string(), "3/5");
+        assert_eq!(
+            checklist[0].children()[1].progress().to_string(),
+            "2/2"
+        );
+
+        checklist.reset();
+        assert_eq!(checklist.progress().done, 0);
+    }
+
+    #[test]
+    fn keeps_the_order_of_checkboxes() {
+        let numbers = (0..=5)
+            .map(|x| x.to_string())
+            .map(Checkbox::with_description);
+        let mut checkboxes: Checklist = numbers.collect();
+        let order = |checkboxes: &Checklist| {
+            checkboxes
+                .iter()
+                .map(Checkbox::description)
+                .collect::<Vec<_>>()
+                .join("")
+        };
+
+        checkboxes.remove(1);
+        assert_eq!(order(&checkboxes), "02345");
+
+        checkboxes
+            .insert(0, Checkbox::with_description("1".into()));
+        assert_eq!(order(&checkboxes), "102345");
+
+        checkboxes.move_item(0, 5);
+        assert_eq!(order(&checkboxes), "023451");
+
+        checkboxes.get_mut(0).unwrap().toggle();
+        checkboxes.get_mut(3).unwrap().toggle();
+        checkboxes.sort_by_status();
+        assert_eq!(order(&checkboxes), "235104");
+
+        checkboxes.retain(|checkbox| !checkbox.is_done());
+        assert_eq!(order(&checkboxes), "2351");
+    }
 }
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+
+use crate::Timezone;
+
+/// Tells the current date and time.
+///
+/// Everything that depends on what day it is takes a clock
+/// rather than asking the system, so that it can be tested
+/// across midnight or replayed on a past day.
+pub trait Clock {
+    /// The current date and time.
+    fn now(&self) -> DateTime<FixedOffset>;
+
+    /// The current date, in the timezone of [`now`](Clock::now).
+    fn today(&self) -> NaiveDate {
+        self.now().date().naive_local()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> DateTime<FixedOffset> {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Box<C> {
+    fn now(&self) -> DateTime<FixedOffset> {
+        (**self).now()
+    }
+}
+
+/// The system's clock, as seen from a given timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemClock {
+    timezone: Timezone,
+}
+
+impl SystemClock {
+    pub fn new(timezone: Timezone) -> Self {
+        Self { timezone }
+    }
+
+    /// The system's clock in the timezone the system is in.
+    pub fn local() -> Self {
+        Self::new(Timezone::local())
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        Utc::now().with_timezone(&self.timezone.offset())
+    }
+}
+
+/// A clock that is stopped at a given moment.
+///
+/// ```rust
+/// # use dodo_internals::{chrono::{FixedOffset, NaiveDate, TimeZone}, Clock, FixedClock};
+/// let clock = FixedClock::new(FixedOffset::east(0).ymd(2023, 1, 2).and_hms(23, 59, 0));
+/// assert_eq!(clock.today(), NaiveDate::from_ymd(2023, 1, 2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(DateTime<FixedOffset>);
+
+impl FixedClock {
+    pub fn new(now: DateTime<FixedOffset>) -> Self {
+        Self(now)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.0
+    }
+}
+
+/// A clock that runs at the same pace as another one, but is
+/// ahead of it (or behind it) by a fixed amount of time.
+///
+/// ```rust
+/// # use dodo_internals::{chrono::{Duration, FixedOffset, NaiveDate, TimeZone}, Clock, FixedClock, OffsetClock};
+/// let clock = FixedClock::new(FixedOffset::east(0).ymd(2023, 1, 2).and_hms(23, 59, 0));
+/// let clock = OffsetClock::new(clock, Duration::minutes(2));
+/// assert_eq!(clock.today(), NaiveDate::from_ymd(2023, 1, 3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetClock<C> {
+    inner: C,
+    offset: Duration,
+}
+
+impl<C: Clock> OffsetClock<C> {
+    pub fn new(inner: C, offset: Duration) -> Self {
+        Self { inner, offset }
+    }
+
+    /// Shifts `inner` by whole days so that it's `date` today,
+    /// keeping the time of day.
+    pub fn on_date(inner: C, date: NaiveDate) -> Self {
+        let offset = date - inner.today();
+        Self::new(inner, offset)
+    }
+}
+
+impl<C: Clock> Clock for OffsetClock<C> {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.inner.now() + self.offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, FixedOffset, NaiveDate, TimeZone};
+
+    use super::{Clock, FixedClock, OffsetClock, SystemClock};
+    use crate::Timezone;
+
+    #[test]
+    fn shifts_to_other_days() {
+        let tokyo = FixedOffset::east(9 * 60 * 60);
+        let clock = FixedClock::new(
+            tokyo.ymd(2023, 3, 1).and_hms(8, 30, 0),
+        );
+
+        let replay = OffsetClock::on_date(
+            clock,
+            NaiveDate::from_ymd(2022, 12, 31),
+        );
+        assert_eq!(
+            replay.now(),
+            tokyo.ymd(2022, 12, 31).and_hms(8, 30, 0)
+        );
+
+        let late = OffsetClock::new(clock, Duration::hours(16));
+        assert_eq!(
+            late.today(),
+            NaiveDate::from_ymd(2023, 3, 2)
+        );
+    }
+
+    #[test]
+    fn follows_the_timezone_of_the_system_clock() {
+        let timezone: Timezone = "-03:00".parse().unwrap();
+        let now = SystemClock::new(timezone).now();
+
+        assert_eq!(*now.offset(), timezone.offset());
+    }
+}
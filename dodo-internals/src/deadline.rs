@@ -0,0 +1,296 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveTime, Offset, TimeZone,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::Task;
+
+/// A fixed offset from UTC, such as "+02:00".
+///
+/// Written as a sign followed by hours and minutes. "Z" and
+/// "UTC" are accepted as well.
+///
+/// ```rust
+/// # use dodo_internals::Timezone;
+/// let timezone: Timezone = "-03:30".parse().unwrap();
+/// assert_eq!(timezone.offset().local_minus_utc(), -(3 * 60 + 30) * 60);
+/// assert_eq!(timezone.to_string(), "-03:30");
+/// assert_eq!("Z".parse(), Ok(Timezone::utc()));
+/// ```
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct Timezone(FixedOffset);
+
+impl Timezone {
+    /// Coordinated Universal Time
+    pub fn utc() -> Self {
+        Self(FixedOffset::east(0))
+    }
+
+    /// The offset the system's clock is currently in.
+    pub fn local() -> Self {
+        Self(Local::now().offset().fix())
+    }
+
+    /// The offset from UTC of this timezone.
+    pub fn offset(self) -> FixedOffset {
+        self.0
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0.local_minus_utc();
+        let sign = if seconds < 0 { '-' } else { '+' };
+        let minutes = seconds.abs() / 60;
+
+        write!(
+            f,
+            "{sign}{:02}:{:02}",
+            minutes / 60,
+            minutes % 60
+        )
+    }
+}
+
+/// Accepts "Z", "UTC", and offsets such as "+02:00", "-0330"
+/// or "+05".
+impl FromStr for Timezone {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.eq_ignore_ascii_case("z")
+            || input.eq_ignore_ascii_case("utc")
+        {
+            return Ok(Self::utc());
+        }
+
+        let (sign, offset) = if let Some(offset) =
+            input.strip_prefix('+')
+        {
+            (1, offset)
+        } else if let Some(offset) = input.strip_prefix('-') {
+            (-1, offset)
+        } else {
+            return Err(());
+        };
+        if !offset.is_ascii() {
+            return Err(());
+        }
+
+        let is_digits = |text: &str| {
+            text.len() == 2
+                && text.chars().all(|ch| ch.is_ascii_digit())
+        };
+        let (hours, minutes) = match offset.len() {
+            2 => (offset, "00"),
+            4 => offset.split_at(2),
+            5 if offset.as_bytes()[2] == b':' => {
+                (&offset[..2], &offset[3..])
+            }
+            _ => return Err(()),
+        };
+        if !is_digits(hours) || !is_digits(minutes) {
+            return Err(());
+        }
+        let hours: i32 = hours.parse().map_err(drop)?;
+        let minutes: i32 = minutes.parse().map_err(drop)?;
+        if hours > 23 || minutes > 59 {
+            return Err(());
+        }
+
+        FixedOffset::east_opt(sign * (hours * 60 + minutes) * 60)
+            .map(Self)
+            .ok_or(())
+    }
+}
+
+impl From<FixedOffset> for Timezone {
+    fn from(offset: FixedOffset) -> Self {
+        Self(offset)
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = String;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input.parse().map_err(|_| {
+            format!(
+                "invalid timezone '{input}', expected an offset \
+                 from UTC such as '+02:00'"
+            )
+        })
+    }
+}
+
+impl From<Timezone> for String {
+    fn from(timezone: Timezone) -> Self {
+        timezone.to_string()
+    }
+}
+
+/// The time of day a task is due at, either in a specific
+/// timezone or in whichever one the user is in.
+///
+/// ```rust
+/// # use dodo_internals::DueTime;
+/// let due: DueTime = "15:00+02:00".parse().unwrap();
+/// assert_eq!(due.to_string(), "15:00+02:00");
+/// assert_eq!("9:30".parse::<DueTime>().unwrap().to_string(), "09:30");
+/// ```
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy,
+)]
+pub struct DueTime {
+    /// The time of day, up to the minute
+    pub time: NaiveTime,
+    /// The timezone the time is in, if it's a specific one
+    pub timezone: Option<Timezone>,
+}
+
+impl Display for DueTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.time.format("%H:%M"))?;
+        if let Some(timezone) = self.timezone {
+            write!(f, "{timezone}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Accepts a time such as "15:00", optionally followed by a
+/// [timezone](Timezone) such as "15:00+02:00" or "15:00Z".
+impl FromStr for DueTime {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (time, timezone) = match input.find(['+', '-', 'Z'])
+        {
+            Some(start) => {
+                (&input[..start], Some(input[start..].parse()?))
+            }
+            None => (input, None),
+        };
+
+        let time = NaiveTime::parse_from_str(time, "%H:%M")
+            .map_err(drop)?;
+
+        Ok(Self { time, timezone })
+    }
+}
+
+impl Task {
+    /// The moment this task is due, if it has a due date.
+    ///
+    /// Due times that aren't in a specific timezone are taken to
+    /// be in `timezone`, and tasks without a due time are due by
+    /// the end of their due date.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Priority, Task, Timezone};
+    /// let mut task = Task::new("Submit taxes".into(), Priority::High, NaiveDate::from_ymd(2023, 1, 1));
+    /// task.due_date = Some(NaiveDate::from_ymd(2023, 4, 15));
+    /// task.due_time = Some("09:00".parse().unwrap());
+    ///
+    /// let deadline = task.deadline(Timezone::utc()).unwrap();
+    /// assert_eq!(deadline.to_rfc3339(), "2023-04-15T09:00:00+00:00");
+    /// ```
+    pub fn deadline(
+        &self,
+        timezone: Timezone,
+    ) -> Option<DateTime<FixedOffset>> {
+        let date = self.due_date?;
+
+        let (time, timezone) = match self.due_time {
+            Some(due) => {
+                (due.time, due.timezone.unwrap_or(timezone))
+            }
+            None => (NaiveTime::from_hms(23, 59, 59), timezone),
+        };
+
+        timezone
+            .offset()
+            .from_local_datetime(&date.and_time(time))
+            .single()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone};
+
+    use super::{DueTime, Timezone};
+    use crate::{Priority, Task};
+
+    fn task_due_at(time: &str) -> Task {
+        let date = NaiveDate::from_ymd(2023, 1, 2);
+        let mut task = Task::new(
+            "Call the bank".into(),
+            Priority::Low,
+            date,
+        );
+        task.due_date = Some(date);
+        task.due_time = Some(time.parse().unwrap());
+
+        task
+    }
+
+    #[test]
+    fn parses_timezones_and_times() {
+        for (text, minutes) in
+            [("+02:00", 120), ("-0330", -210), ("+05", 300)]
+        {
+            let timezone: Timezone = text.parse().unwrap();
+            assert_eq!(
+                timezone.offset().local_minus_utc(),
+                minutes * 60
+            );
+        }
+
+        for invalid in
+            ["", "02:00", "+24:00", "+2:0", "+a€", "é"]
+        {
+            assert!(invalid.parse::<Timezone>().is_err());
+        }
+
+        assert_eq!(
+            "15:00Z".parse(),
+            Ok(DueTime {
+                time: chrono::NaiveTime::from_hms(15, 0, 0),
+                timezone: Some(Timezone::utc()),
+            })
+        );
+        assert!("25:00".parse::<DueTime>().is_err());
+        assert!("15:00+2".parse::<DueTime>().is_err());
+    }
+
+    #[test]
+    fn accounts_for_time_of_day_and_timezones() {
+        let morning = task_due_at("09:00");
+        let afternoon = task_due_at("15:00");
+        assert!(morning.cmp_pressing(&afternoon).is_gt());
+
+        // 15:00 in UTC+08:00 is 07:00 in UTC
+        let far_east = task_due_at("15:00+08:00");
+        assert!(far_east.cmp_pressing(&morning).is_gt());
+
+        let utc = Timezone::utc().offset();
+        let now = utc.ymd(2023, 1, 2).and_hms(8, 0, 0);
+        assert!(far_east.is_overdue(now));
+        assert!(!morning.is_overdue(now));
+
+        // Due times without a timezone follow the one of `now`
+        let tokyo: Timezone = "+09:00".parse().unwrap();
+        assert!(morning
+            .is_overdue(now.with_timezone(&tokyo.offset())));
+    }
+}
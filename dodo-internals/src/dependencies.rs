@@ -0,0 +1,240 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
+
+use crate::{Task, TaskId, TaskSet};
+
+/// A chain of tasks that depend on each other in a loop, so that
+/// none of them can ever start. The first and last IDs are the
+/// same.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Cycle(pub Vec<TaskId>);
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids: Vec<_> =
+            self.0.iter().map(TaskId::short).collect();
+
+        write!(f, "{}", ids.join(" → "))
+    }
+}
+
+/// Dependencies between the tasks of a set.
+///
+/// Dependencies on tasks that are closed, whether done or
+/// cancelled, count as satisfied, and so do dependencies on
+/// tasks that aren't in the set (such as closed tasks that were
+/// left behind on a previous day).
+impl TaskSet {
+    /// Returns the tasks of this set that `task` depends on.
+    pub fn dependencies<'a>(
+        &'a self,
+        task: &'a Task,
+    ) -> impl Iterator<Item = &'a Task> {
+        task.depends_on
+            .iter()
+            .filter_map(move |&id| self.get_by_id(id))
+    }
+
+    /// Returns the tasks of this set that `task` depends on and
+    /// that are still open.
+    pub fn blockers<'a>(
+        &'a self,
+        task: &'a Task,
+    ) -> impl Iterator<Item = &'a Task> {
+        self.dependencies(task)
+            .filter(|dependency| !dependency.status.is_closed())
+    }
+
+    /// Returns true if any of the tasks `task` depends on is
+    /// still open.
+    pub fn is_blocked(&self, task: &Task) -> bool {
+        self.blockers(task).next().is_some()
+    }
+
+    /// Returns the open tasks whose dependencies are all closed,
+    /// meaning that they can be worked on right away.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Priority, Status, Task, TaskSet};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Write report".into(), Priority::Low, today));
+    /// set.push(Task::new("Send report".into(), Priority::Low, today));
+    /// let write = set.0[0].id;
+    /// set.0[1].depends_on.push(write);
+    ///
+    /// let ready: Vec<_> = set.ready().map(|task| task.idx).collect();
+    /// assert_eq!(ready, [1]);
+    ///
+    /// set.0[0].status = Status::Done;
+    /// let ready: Vec<_> = set.ready().map(|task| task.idx).collect();
+    /// assert_eq!(ready, [2]);
+    /// ```
+    pub fn ready(&self) -> impl Iterator<Item = &Task> {
+        self.0.iter().filter(move |task| {
+            !task.status.is_closed() && !self.is_blocked(task)
+        })
+    }
+
+    /// Orders the tasks of this set so that every task comes
+    /// after the tasks it depends on. Tasks that don't depend on
+    /// each other keep their relative order.
+    ///
+    /// Fails if some tasks depend on each other in a loop.
+    pub fn topological_order(
+        &self,
+    ) -> Result<Vec<&Task>, Cycle> {
+        let mut remaining: Vec<_> = self.0.iter().collect();
+        let mut ordered: Vec<&Task> =
+            Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let is_ordered = |id: TaskId| {
+                ordered.iter().any(|task| task.id == id)
+            };
+            let next = remaining.iter().position(|task| {
+                self.dependencies(task)
+                    .all(|dependency| is_ordered(dependency.id))
+            });
+
+            match next {
+                Some(pos) => ordered.push(remaining.remove(pos)),
+                None => {
+                    return Err(self.find_cycle().expect(
+                        "tasks left unordered form a cycle",
+                    ))
+                }
+            }
+        }
+
+        Ok(ordered)
+    }
+
+    /// Returns a chain of tasks that depend on each other in a
+    /// loop, if there's any.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Cycle, Priority, Task, TaskSet};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Chicken".into(), Priority::Low, today));
+    /// set.push(Task::new("Egg".into(), Priority::Low, today));
+    /// assert_eq!(set.find_cycle(), None);
+    ///
+    /// let (chicken, egg) = (set.0[0].id, set.0[1].id);
+    /// set.0[0].depends_on.push(egg);
+    /// set.0[1].depends_on.push(chicken);
+    /// assert_eq!(set.find_cycle(), Some(Cycle(vec![chicken, egg, chicken])));
+    /// ```
+    pub fn find_cycle(&self) -> Option<Cycle> {
+        let mut finished = HashSet::new();
+
+        self.0.iter().find_map(|task| {
+            self.find_cycle_from(
+                task,
+                &mut vec![],
+                &mut finished,
+            )
+        })
+    }
+
+    /// Depth-first search for a cycle going through `task`, where
+    /// `path` holds the tasks that led to it and `finished` the
+    /// tasks known not to be part of any cycle.
+    fn find_cycle_from(
+        &self,
+        task: &Task,
+        path: &mut Vec<TaskId>,
+        finished: &mut HashSet<TaskId>,
+    ) -> Option<Cycle> {
+        if let Some(start) =
+            path.iter().position(|&id| id == task.id)
+        {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(task.id);
+            return Some(Cycle(cycle));
+        }
+
+        if finished.contains(&task.id) {
+            return None;
+        }
+
+        path.push(task.id);
+        for dependency in self.dependencies(task) {
+            let cycle =
+                self.find_cycle_from(dependency, path, finished);
+            if cycle.is_some() {
+                return cycle;
+            }
+        }
+        path.pop();
+        finished.insert(task.id);
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{test_utils::tasks, Status, TaskId};
+
+    #[test]
+    fn orders_tasks_after_their_dependencies() {
+        let mut set =
+            tasks(&["Deploy", "Write code", "Test", "Lunch"]);
+        let id = |pos: usize| set.0[pos].id;
+        let (deploy, code, test) = (id(0), id(1), id(2));
+
+        set.0[0].depends_on = vec![test, code];
+        set.0[2].depends_on = vec![code, TaskId::generate()];
+
+        let order: Vec<_> = set
+            .topological_order()
+            .unwrap()
+            .into_iter()
+            .map(|task| task.name.as_str())
+            .collect();
+        assert_eq!(
+            order,
+            ["Write code", "Test", "Deploy", "Lunch"]
+        );
+
+        assert!(set.is_blocked(&set.0[0]));
+        assert!(!set.is_blocked(&set.0[1]));
+
+        set.0[1].depends_on.push(deploy);
+        let cycle = set.topological_order().unwrap_err();
+        assert_eq!(cycle.0.first(), cycle.0.last());
+        assert_eq!(cycle.0.len(), 4);
+    }
+
+    #[test]
+    fn finds_tasks_depending_on_themselves() {
+        let mut set = tasks(&["Procrastinate"]);
+        let id = set.0[0].id;
+        set.0[0].depends_on.push(id);
+
+        assert_eq!(set.find_cycle().unwrap().0, [id, id]);
+        assert_eq!(set.ready().count(), 0);
+    }
+
+    #[test]
+    fn lists_unfinished_dependencies_as_blockers() {
+        let mut set = tasks(&["Deploy", "Write code", "Test"]);
+        let (code, test) = (set.0[1].id, set.0[2].id);
+        set.0[0].depends_on = vec![code, test];
+        set.0[2].status = Status::Cancelled;
+
+        let blockers: Vec<_> = set
+            .blockers(&set.0[0])
+            .map(|task| task.name.as_str())
+            .collect();
+        assert_eq!(blockers, ["Write code"]);
+
+        set.0[1].status = Status::Done;
+        assert!(!set.is_blocked(&set.0[0]));
+    }
+}
@@ -0,0 +1,136 @@
+//! The format task lists were stored in before tasks had
+//! identifiers, statuses and everything else that came after
+//! them. Day files written back then can still be read by
+//! converting them into today's [`TaskSet`].
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::{
+    Checkbox, Checklist, Priority, Status, Task, TaskId, TaskSet,
+};
+
+/// A task as it used to be stored.
+#[derive(Debug, Deserialize)]
+pub struct LegacyTask {
+    idx: usize,
+    name: String,
+    is_done: bool,
+    creation_date: NaiveDate,
+    due_date: Option<NaiveDate>,
+    priority: Priority,
+    checklist: LegacyChecklist,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyChecklist {
+    checkboxes: Vec<LegacyCheckbox>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyCheckbox {
+    description: String,
+    is_done: bool,
+}
+
+/// Gives the task the same identifier every time it's read,
+/// so that the copies of a task in the files of consecutive
+/// days are known to be the same task.
+impl From<LegacyTask> for Task {
+    fn from(legacy: LegacyTask) -> Self {
+        let mut task = Task::new(
+            legacy.name,
+            legacy.priority,
+            legacy.creation_date,
+        );
+        task.idx = legacy.idx;
+        task.id = TaskId::derive(&[
+            &task.creation_date.to_string(),
+            &task.name,
+        ]);
+        task.due_date = legacy.due_date;
+        if legacy.is_done {
+            // When it was done is unknown
+            task.status = Status::Done;
+        }
+        task.checklist = legacy
+            .checklist
+            .checkboxes
+            .into_iter()
+            .map(|checkbox| {
+                Checkbox::with_description(checkbox.description)
+                    .with_status(checkbox.is_done)
+            })
+            .collect::<Checklist>();
+
+        task
+    }
+}
+
+/// Tasks created on the same day with the same name would get
+/// the same identifier, so all but the first one also count how
+/// many such tasks came before them. Like the first one, they
+/// get the same identifier every time they're read.
+impl From<Vec<LegacyTask>> for TaskSet {
+    fn from(legacy: Vec<LegacyTask>) -> Self {
+        let mut seen = HashMap::new();
+        let tasks = legacy
+            .into_iter()
+            .map(Task::from)
+            .map(|mut task| {
+                let nth = seen.entry(task.id).or_insert(0);
+                if *nth > 0 {
+                    task.id = TaskId::derive(&[
+                        &task.creation_date.to_string(),
+                        &task.name,
+                        &nth.to_string(),
+                    ]);
+                }
+                *nth += 1;
+                task
+            })
+            .collect();
+
+        TaskSet(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LegacyChecklist, LegacyTask};
+    use crate::{test_utils::today, Priority, TaskSet};
+
+    fn legacy_task(idx: usize, name: &str) -> LegacyTask {
+        LegacyTask {
+            idx,
+            name: name.into(),
+            is_done: false,
+            creation_date: today(),
+            due_date: None,
+            priority: Priority::Low,
+            checklist: LegacyChecklist { checkboxes: vec![] },
+        }
+    }
+
+    #[test]
+    fn tells_duplicates_apart_the_same_way_every_time() {
+        let read = || {
+            TaskSet::from(vec![
+                legacy_task(1, "Water plants"),
+                legacy_task(2, "Water plants"),
+                legacy_task(3, "Water plants"),
+            ])
+        };
+
+        let tasks = read();
+        let ids: Vec<_> =
+            tasks.0.iter().map(|task| task.id).collect();
+        assert_ne!(ids[0], ids[1]);
+        assert_ne!(ids[1], ids[2]);
+        assert_ne!(ids[0], ids[2]);
+
+        assert_eq!(read(), tasks);
+    }
+}
@@ -1,13 +1,47 @@
+mod attributes;
+mod change;
 mod checkbox;
 mod checklist;
+mod clock;
+mod deadline;
+mod dependencies;
+mod legacy;
 mod priority;
+mod query;
+mod recurrence;
+mod rollover;
+mod status;
 mod task;
+mod task_id;
 mod task_set;
+#[cfg(test)]
+mod test_utils;
+mod time_log;
+mod time_report;
+mod urgency;
 pub mod utils;
+mod validation;
 
+pub use attributes::{
+    AttributeKind, AttributeSchema, AttributeValue,
+};
+pub use change::Change;
 pub use checkbox::Checkbox;
-pub use checklist::Checklist;
+pub use checklist::{Checklist, Progress};
 pub use chrono;
+pub use clock::{Clock, FixedClock, OffsetClock, SystemClock};
+pub use deadline::{DueTime, Timezone};
+pub use dependencies::Cycle;
+pub use legacy::LegacyTask;
 pub use priority::Priority;
+pub use query::{Condition, Query, QueryDay};
+pub use recurrence::{Frequency, Recurrence};
+pub use rollover::{Rollover, RolloverPolicy};
+pub use status::Status;
 pub use task::Task;
+pub use task_id::TaskId;
 pub use task_set::TaskSet;
+pub use time_log::TimeEntry;
+pub use time_report::{DayTime, TaskTime, TimeReport};
+pub use urgency::UrgencyWeights;
+pub use validation::ValidationIssue;
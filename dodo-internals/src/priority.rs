@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, fmt::Display};
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
@@ -28,12 +28,31 @@ impl Priority {
         }
     }
 
-    pub fn from_str(input: &str) -> Option<Self> {
+    /// The priority one level above this one, or this one if
+    /// it's already the highest.
+    ///
+    /// ```rust
+    /// # use dodo_internals::Priority;
+    /// assert_eq!(Priority::Low.raised(), Priority::Medium);
+    /// assert_eq!(Priority::High.raised(), Priority::High);
+    /// ```
+    pub fn raised(self) -> Self {
+        match self {
+            Priority::High | Priority::Medium => Priority::High,
+            Priority::Low => Priority::Medium,
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
         match input {
-            "HIGH" | "high" => Some(Self::High),
-            "MEDIUM" | "mediums" => Some(Self::Medium),
-            "LOW" | "low" => Some(Self::Low),
-            _ => None,
+            "HIGH" | "high" => Ok(Self::High),
+            "MEDIUM" | "medium" => Ok(Self::Medium),
+            "LOW" | "low" => Ok(Self::Low),
+            _ => Err(()),
         }
     }
 }
@@ -47,20 +66,26 @@ impl Display for Priority {
     }
 }
 
-impl PartialOrd for Priority {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> Ordering {
         use Priority::*;
 
         match (self, other) {
-            (x, y) if x == y => Some(Ordering::Equal),
-            (High, _) => Some(Ordering::Greater),
-            (Medium, High) => Some(Ordering::Less),
-            (Medium, _) => Some(Ordering::Greater),
-            (Low, _) => Some(Ordering::Less),
+            (x, y) if x == y => Ordering::Equal,
+            (High, _) => Ordering::Greater,
+            (Medium, High) => Ordering::Less,
+            (Medium, _) => Ordering::Greater,
+            (Low, _) => Ordering::Less,
         }
     }
 }
 
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -0,0 +1,493 @@
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::{
+    task_set::latest_versions,
+    utils::{parse_word, MAX_DAY_OFFSET},
+    Priority, Status, Task, TaskSet,
+};
+
+/// A day that a query compares dates to, either a fixed date or
+/// a number of days away from today.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QueryDay {
+    /// Written as "2023-01-02"
+    On(NaiveDate),
+    /// Written as "today", "tomorrow", "yesterday", "+7d", "-3d"
+    /// or "+2w"
+    InDays(i64),
+}
+
+impl QueryDay {
+    /// The date this day falls on when it's `today`.
+    pub fn resolve(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            QueryDay::On(date) => date,
+            QueryDay::InDays(days) => {
+                today + Duration::days(days)
+            }
+        }
+    }
+}
+
+impl FromStr for QueryDay {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "today" => return Ok(QueryDay::InDays(0)),
+            "tomorrow" => return Ok(QueryDay::InDays(1)),
+            "yesterday" => return Ok(QueryDay::InDays(-1)),
+            _ => {}
+        }
+
+        if let Ok(date) =
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        {
+            return Ok(QueryDay::On(date));
+        }
+
+        let offset = |input: &str| -> Option<i64> {
+            let (sign, rest) = match input.strip_prefix('-') {
+                Some(rest) => (-1, rest),
+                None => {
+                    (1, input.strip_prefix('+').unwrap_or(input))
+                }
+            };
+            let (count, unit) = rest.split_at(
+                rest.find(|ch: char| !ch.is_ascii_digit())?,
+            );
+            let count: i64 = count.parse().ok()?;
+            let days = match unit {
+                "d" => count,
+                "w" => count.checked_mul(7)?,
+                _ => return None,
+            };
+
+            (days <= MAX_DAY_OFFSET).then_some(sign * days)
+        };
+
+        offset(input).map(QueryDay::InDays).ok_or_else(|| {
+            format!(
+                "invalid day '{input}', expected a date as \
+                 YYYY-MM-DD, 'today', 'tomorrow', 'yesterday' or \
+                 a number of days or weeks such as '+7d' or '-2w'"
+            )
+        })
+    }
+}
+
+/// A single condition of a [`Query`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Condition {
+    /// Written as "priority:high"
+    Priority(Priority),
+    /// Written as "status:done" or any other status
+    Status(Status),
+    /// Tasks that aren't done or cancelled, written as
+    /// "status:pending" or "status:open"
+    Open,
+    /// Written as "due:2023-01-02" or "due:today"
+    DueOn(QueryDay),
+    /// Written as "due.before:+7d"
+    DueBefore(QueryDay),
+    /// Written as "due.after:2023-01-02"
+    DueAfter(QueryDay),
+    /// Written as "#errands" or "tag:errands"
+    Tag(String),
+    /// Written as "+website"
+    Project(String),
+    /// Written as "@home"
+    Context(String),
+    /// Tasks whose name contains some text regardless of case,
+    /// written as "name~deploy"
+    NameContains(String),
+    /// A custom attribute along with its value, written as
+    /// "ticket:ENG-123"
+    Attribute(String, String),
+    /// Tasks that don't meet a condition, written as the
+    /// condition preceded by '!', as in "!#errands". Written as
+    /// "status:closed" for closed tasks
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Returns true if the given task meets this condition when
+    /// it's `today`.
+    pub fn matches(
+        &self,
+        task: &Task,
+        today: NaiveDate,
+    ) -> bool {
+        let due = |check: &dyn Fn(NaiveDate) -> bool| {
+            task.due_date.is_some_and(check)
+        };
+
+        match self {
+            Condition::Priority(priority) => {
+                task.priority == *priority
+            }
+            Condition::Status(status) => task.status == *status,
+            Condition::Open => !task.status.is_closed(),
+            Condition::DueOn(day) => {
+                due(&|date| date == day.resolve(today))
+            }
+            Condition::DueBefore(day) => {
+                due(&|date| date < day.resolve(today))
+            }
+            Condition::DueAfter(day) => {
+                due(&|date| date > day.resolve(today))
+            }
+            Condition::Tag(tag) => task.tags.contains(tag),
+            Condition::Project(project) => {
+                task.project.as_ref() == Some(project)
+            }
+            Condition::Context(context) => {
+                task.context.as_ref() == Some(context)
+            }
+            Condition::NameContains(text) => {
+                task.name.to_lowercase().contains(text)
+            }
+            // The value is read as the same type as the task's,
+            // so that "points:3" matches "points:3.0"
+            Condition::Attribute(key, value) => {
+                task.attributes.get(key).is_some_and(|actual| {
+                    actual.kind().parse(value).as_ref()
+                        == Some(actual)
+                })
+            }
+            Condition::Not(condition) => {
+                !condition.matches(task, today)
+            }
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Some(condition) = input.strip_prefix('!') {
+            return condition.parse().map(|condition| {
+                Condition::Not(Box::new(condition))
+            });
+        }
+
+        // '#' starts a comment in most shells, so tags may also be
+        // written with a "tag:" prefix
+        if let Some(tag) = input.strip_prefix('#') {
+            return parse_word(tag).map(Condition::Tag);
+        }
+        if let Some(project) = input.strip_prefix('+') {
+            return parse_word(project).map(Condition::Project);
+        }
+        if let Some(context) = input.strip_prefix('@') {
+            return parse_word(context).map(Condition::Context);
+        }
+        if let Some(text) = input.strip_prefix("name~") {
+            return parse_word(text).map(|text| {
+                Condition::NameContains(text.to_lowercase())
+            });
+        }
+
+        let (key, value) = input
+            .split_once(':')
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(|| {
+                format!(
+                    "invalid condition '{input}', expected '#tag', \
+                     'tag:tag', '+project', '@context', \
+                     'name~text' or 'key:value'"
+                )
+            })?;
+
+        match key {
+            "tag" => parse_word(value).map(Condition::Tag),
+            "priority" => value
+                .to_lowercase()
+                .parse()
+                .map(Condition::Priority)
+                .map_err(|_| {
+                    format!(
+                        "invalid priority '{value}', expected \
+                         one of: high, medium, low"
+                    )
+                }),
+            "status" => match value {
+                "pending" | "open" => Ok(Condition::Open),
+                "closed" => {
+                    Ok(Condition::Not(Box::new(Condition::Open)))
+                }
+                _ => value
+                    .parse()
+                    .map(Condition::Status)
+                    .map_err(|_| {
+                        format!(
+                            "invalid status '{value}', expected \
+                             one of: pending, closed, todo, \
+                             in-progress, blocked, waiting, \
+                             cancelled, done"
+                        )
+                    }),
+            },
+            "due" => value.parse().map(Condition::DueOn),
+            "due.before" => {
+                value.parse().map(Condition::DueBefore)
+            }
+            "due.after" => {
+                value.parse().map(Condition::DueAfter)
+            }
+            _ => parse_word(value).map(|value| {
+                Condition::Attribute(key.to_owned(), value)
+            }),
+        }
+    }
+}
+
+/// Conditions that tasks must all meet, written one after the
+/// other and separated by whitespace.
+///
+/// ```rust
+/// # use dodo_internals::{chrono::NaiveDate, Priority, Query, Task, TaskSet};
+/// let today = NaiveDate::from_ymd(2023, 1, 2);
+/// let mut set = TaskSet(vec![]);
+/// set.push(Task::new("Deploy the API".into(), Priority::High, today));
+/// set.push(Task::new("Deploy the docs".into(), Priority::Low, today));
+/// set.0[0].due_date = Some(NaiveDate::from_ymd(2023, 1, 5));
+/// set.0[0].project = Some("infra".into());
+///
+/// let query: Query = "priority:high due.before:+7d status:pending +infra name~deploy".parse().unwrap();
+/// let names: Vec<_> = set.query(&query, today).map(|task| &task.name).collect();
+/// assert_eq!(names, ["Deploy the API"]);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Query(pub Vec<Condition>);
+
+impl Query {
+    /// Returns true if the given task meets every condition of
+    /// this query when it's `today`. Empty queries match every
+    /// task.
+    pub fn matches(
+        &self,
+        task: &Task,
+        today: NaiveDate,
+    ) -> bool {
+        self.0
+            .iter()
+            .all(|condition| condition.matches(task, today))
+    }
+
+    /// Searches the task sets of many days, from the oldest to
+    /// the newest one, along with the day each one is for.
+    ///
+    /// A task that shows up on many days is only looked at as it
+    /// was on the latest of them, and is returned along with
+    /// that day.
+    pub fn search_history<'a>(
+        &self,
+        history: impl IntoIterator<Item = (NaiveDate, &'a TaskSet)>,
+        today: NaiveDate,
+    ) -> Vec<(NaiveDate, &'a Task)> {
+        let mut latest = latest_versions(history);
+        latest.retain(|(_, task)| self.matches(task, today));
+
+        latest
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        input
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Query)
+    }
+}
+
+impl TaskSet {
+    /// The tasks of this set that match `query` when it's
+    /// `today`.
+    pub fn query<'a>(
+        &'a self,
+        query: &'a Query,
+        today: NaiveDate,
+    ) -> impl Iterator<Item = &'a Task> {
+        self.0
+            .iter()
+            .filter(move |task| query.matches(task, today))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDate, TimeZone};
+
+    use super::{Condition, Query, QueryDay};
+    use crate::{
+        test_utils::today, AttributeValue, Priority, Status,
+        Task, TaskSet,
+    };
+
+    #[test]
+    fn parses_conditions() {
+        assert_eq!(
+            "#errands".parse(),
+            Ok(Condition::Tag("errands".into()))
+        );
+        assert_eq!(
+            "tag:errands".parse(),
+            Ok(Condition::Tag("errands".into()))
+        );
+        assert_eq!(
+            "+website".parse(),
+            Ok(Condition::Project("website".into()))
+        );
+        assert_eq!(
+            "@home".parse(),
+            Ok(Condition::Context("home".into()))
+        );
+        assert_eq!(
+            "ticket:ENG-123".parse(),
+            Ok(Condition::Attribute(
+                "ticket".into(),
+                "ENG-123".into()
+            ))
+        );
+        assert_eq!(
+            "!status:done".parse(),
+            Ok(Condition::Not(Box::new(Condition::Status(
+                Status::Done
+            ))))
+        );
+        assert_eq!(
+            "due.before:+7d".parse(),
+            Ok(Condition::DueBefore(QueryDay::InDays(7)))
+        );
+        assert_eq!("-2w".parse(), Ok(QueryDay::InDays(-14)));
+
+        for invalid in [
+            "errands",
+            "+",
+            "ticket:",
+            "priority:urgent",
+            "status:finished",
+            "due:soon",
+            "due:+7y",
+            "due:+99999999999999999d",
+        ] {
+            assert!(
+                invalid.parse::<Condition>().is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn matches_tasks() {
+        let mut task =
+            Task::new("Buy milk".into(), Priority::Low, today());
+        task.tags.insert("errands".into());
+        task.context = Some("store".into());
+        task.attributes.insert(
+            "points".into(),
+            AttributeValue::Number(3.0),
+        );
+
+        let matches = |query: &str| {
+            query
+                .parse::<Query>()
+                .unwrap()
+                .matches(&task, today())
+        };
+        assert!(matches("#errands @store name~MILK"));
+        assert!(matches("points:3.0 status:pending !+website"));
+        assert!(matches(""));
+        assert!(!matches("@home"));
+        assert!(!matches("points:4"));
+        assert!(!matches("status:closed"));
+        // Tasks without a due date never match due dates
+        assert!(!matches("due.before:+7d"));
+        assert!(!matches("due.after:-7d"));
+    }
+
+    #[test]
+    fn compares_due_dates_to_today() {
+        let mut task =
+            Task::new("Pay rent".into(), Priority::Low, today());
+        task.due_date = Some(NaiveDate::from_ymd(2023, 1, 5));
+
+        let matches = |query: &str, today| {
+            query.parse::<Query>().unwrap().matches(&task, today)
+        };
+        assert!(matches("due.before:+7d", today()));
+        assert!(matches("due.after:today", today()));
+        assert!(matches(
+            "due:tomorrow",
+            NaiveDate::from_ymd(2023, 1, 4)
+        ));
+        assert!(matches("due:2023-01-05", today()));
+        assert!(!matches("due.before:+3d", today()));
+        assert!(!matches(
+            "due.after:yesterday",
+            NaiveDate::from_ymd(2023, 1, 7)
+        ));
+    }
+
+    #[test]
+    fn searches_the_latest_version_of_each_task() {
+        let monday = NaiveDate::from_ymd(2023, 1, 2);
+        let tuesday = NaiveDate::from_ymd(2023, 1, 3);
+
+        let mut first = TaskSet(vec![]);
+        first.push(Task::new(
+            "Deploy".into(),
+            Priority::High,
+            monday,
+        ));
+        first.push(Task::new(
+            "Old news".into(),
+            Priority::High,
+            monday,
+        ));
+        let mut second = TaskSet(vec![first.0[0].clone()]);
+        second.0[0].set_status(
+            Status::Done,
+            FixedOffset::east(0)
+                .from_utc_datetime(&tuesday.and_hms(9, 0, 0)),
+        );
+
+        let query: Query = "priority:high".parse().unwrap();
+        let found = query.search_history(
+            [(monday, &first), (tuesday, &second)],
+            tuesday,
+        );
+        let found: Vec<_> = found
+            .iter()
+            .map(|(date, task)| {
+                (*date, task.name.as_str(), task.status)
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (tuesday, "Deploy", Status::Done),
+                (monday, "Old news", Status::Todo)
+            ]
+        );
+
+        let query: Query = "status:pending".parse().unwrap();
+        assert_eq!(
+            query
+                .search_history(
+                    [(monday, &first), (tuesday, &second)],
+                    tuesday
+                )
+                .len(),
+            1
+        );
+    }
+}
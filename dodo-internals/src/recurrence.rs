@@ -0,0 +1,295 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// How often a recurring task comes back.
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, Clone,
+)]
+pub enum Frequency {
+    /// Every day
+    Daily,
+    /// Every week, on each of the given weekdays
+    Weekly(Vec<Weekday>),
+    /// Every month, on the given day of the month. Months that
+    /// are too short get their last day instead
+    Monthly(u32),
+    /// Every given amount of days
+    EveryNDays(u32),
+}
+
+/// The rule a recurring task follows, such as "every monday
+/// and thursday until the end of the year".
+///
+/// ```rust
+/// # use dodo_internals::{chrono::NaiveDate, Recurrence};
+/// let recurrence: Recurrence = "month:31".parse().unwrap();
+/// let jan_31 = NaiveDate::from_ymd(2023, 1, 31);
+///
+/// let next = recurrence.next_after(jan_31);
+/// assert_eq!(next, Some(NaiveDate::from_ymd(2023, 2, 28)));
+/// ```
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, Clone,
+)]
+pub struct Recurrence {
+    /// How often the task comes back
+    pub frequency: Frequency,
+    /// The last day the task may come back on, if any
+    pub until: Option<NaiveDate>,
+}
+
+impl Recurrence {
+    /// Creates a recurrence with no end date.
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            until: None,
+        }
+    }
+
+    /// Returns the first date after `date` on which the task
+    /// comes back, unless that's past the end date or past the
+    /// last date there can be.
+    pub fn next_after(
+        &self,
+        date: NaiveDate,
+    ) -> Option<NaiveDate> {
+        let next = match &self.frequency {
+            Frequency::Daily => date.succ(),
+            Frequency::Weekly(weekdays) => (1..=7)
+                .map(|days| date + Duration::days(days))
+                .find(|next| {
+                    weekdays.contains(&next.weekday())
+                })?,
+            Frequency::Monthly(day) => {
+                let this_month = day_of_month(
+                    date.year(),
+                    date.month(),
+                    *day,
+                );
+
+                if this_month > date {
+                    this_month
+                } else if date.month() == 12 {
+                    day_of_month(date.year() + 1, 1, *day)
+                } else {
+                    day_of_month(
+                        date.year(),
+                        date.month() + 1,
+                        *day,
+                    )
+                }
+            }
+            Frequency::EveryNDays(days) => date
+                .checked_add_signed(Duration::days(
+                    i64::from(*days).max(1),
+                ))?,
+        };
+
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next),
+        }
+    }
+}
+
+/// The given day of the month, or the last day of the month if
+/// it's too short to have it.
+fn day_of_month(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day.min(31))
+        .rev()
+        .find_map(|day| {
+            NaiveDate::from_ymd_opt(year, month, day)
+        })
+        .unwrap_or_else(|| NaiveDate::from_ymd(year, month, 1))
+}
+
+/// Written as "every:" followed by the frequency, then by
+/// " until:" and the end date if there's one.
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "every:{}", self.frequency)?;
+        if let Some(until) = self.until {
+            write!(f, " until:{until}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frequency::Daily => write!(f, "day"),
+            Frequency::Weekly(weekdays) => {
+                let names: Vec<_> =
+                    weekdays.iter().map(weekday_name).collect();
+                write!(f, "{}", names.join(","))
+            }
+            Frequency::Monthly(day) => write!(f, "month:{day}"),
+            Frequency::EveryNDays(days) => {
+                write!(f, "{days}days")
+            }
+        }
+    }
+}
+
+fn weekday_name(weekday: &Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+}
+
+/// Accepts "day", "3days" (or "3d"), comma-separated weekdays
+/// such as "mon,thu", and "month:15".
+impl FromStr for Frequency {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.to_ascii_lowercase();
+
+        if input == "day" || input == "daily" {
+            return Ok(Frequency::Daily);
+        }
+
+        if let Some(day) = input.strip_prefix("month:") {
+            return match day.parse() {
+                Ok(day @ 1..=31) => Ok(Frequency::Monthly(day)),
+                _ => Err(()),
+            };
+        }
+
+        // Checking for digits keeps "wed" from being taken for
+        // an amount of days
+        let days = input
+            .strip_suffix("days")
+            .or_else(|| input.strip_suffix('d'))
+            .and_then(|days| days.parse().ok());
+        if let Some(days) = days {
+            return match days {
+                0 => Err(()),
+                days => Ok(Frequency::EveryNDays(days)),
+            };
+        }
+
+        let mut weekdays = input
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Weekday>, _>>()
+            .map_err(drop)?;
+        weekdays.sort_by_key(Weekday::num_days_from_monday);
+        weekdays.dedup();
+
+        Ok(Frequency::Weekly(weekdays))
+    }
+}
+
+/// Parses a [`Frequency`], giving it no end date.
+impl FromStr for Recurrence {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        input.parse().map(Recurrence::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Weekday};
+
+    use super::{Frequency, Recurrence};
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2023, month, day)
+    }
+
+    #[test]
+    fn parses_and_displays_frequencies() {
+        let frequencies = [
+            ("day", Frequency::Daily),
+            ("3days", Frequency::EveryNDays(3)),
+            (
+                "mon,thu",
+                Frequency::Weekly(vec![
+                    Weekday::Mon,
+                    Weekday::Thu,
+                ]),
+            ),
+            ("month:15", Frequency::Monthly(15)),
+        ];
+
+        for (text, frequency) in frequencies {
+            assert_eq!(text.parse(), Ok(frequency.clone()));
+            assert_eq!(frequency.to_string(), text);
+        }
+
+        assert_eq!(
+            "thursday,MON".parse(),
+            "mon,thu".parse::<Frequency>()
+        );
+        assert_eq!("2d".parse(), Ok(Frequency::EveryNDays(2)));
+        assert_eq!(
+            "wed".parse(),
+            Ok(Frequency::Weekly(vec![Weekday::Wed]))
+        );
+
+        for invalid in
+            ["", "0days", "month:32", "mon,", "weekly"]
+        {
+            assert!(invalid.parse::<Frequency>().is_err());
+        }
+    }
+
+    #[test]
+    fn finds_next_dates() {
+        // The 2nd of January of 2023 was a monday
+        let weekly = Recurrence::new("mon,thu".parse().unwrap());
+        assert_eq!(
+            weekly.next_after(date(1, 2)),
+            Some(date(1, 5))
+        );
+        assert_eq!(
+            weekly.next_after(date(1, 5)),
+            Some(date(1, 9))
+        );
+
+        let monthly = Recurrence::new(Frequency::Monthly(15));
+        assert_eq!(
+            monthly.next_after(date(1, 2)),
+            Some(date(1, 15))
+        );
+        assert_eq!(
+            monthly.next_after(date(1, 15)),
+            Some(date(2, 15))
+        );
+        assert_eq!(
+            monthly.next_after(date(12, 20)),
+            Some(NaiveDate::from_ymd(2024, 1, 15))
+        );
+
+        let every_ten_days = Recurrence {
+            frequency: Frequency::EveryNDays(10),
+            until: Some(date(1, 20)),
+        };
+        assert_eq!(
+            every_ten_days.next_after(date(1, 2)),
+            Some(date(1, 12))
+        );
+        assert_eq!(every_ten_days.next_after(date(1, 12)), None);
+
+        let every_eon =
+            Recurrence::new(Frequency::EveryNDays(u32::MAX));
+        assert_eq!(every_eon.next_after(date(1, 2)), None);
+    }
+}
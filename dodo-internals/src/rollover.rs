@@ -0,0 +1,405 @@
+use std::fmt::{self, Display};
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{Status, Task, TaskId, TaskSet};
+
+/// The rules followed when the tasks of the last day are moved
+/// over to a new day. Everything is enabled by default.
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, Clone,
+)]
+#[serde(default, deny_unknown_fields)]
+pub struct RolloverPolicy {
+    /// Whether completed tasks are left behind
+    pub drop_completed: bool,
+    /// Whether cancelled tasks are left behind
+    pub drop_cancelled: bool,
+    /// Whether unfinished tasks are moved over
+    pub carry_unfinished: bool,
+    /// Whether the checklists of recurring tasks get unchecked
+    pub reset_recurring_checklists: bool,
+    /// Whether overdue tasks get their priority raised by one
+    /// level
+    pub escalate_overdue: bool,
+    /// Whether tasks get renumbered so that their indices have
+    /// no gaps
+    pub renumber: bool,
+}
+
+impl Default for RolloverPolicy {
+    fn default() -> Self {
+        Self {
+            drop_completed: true,
+            drop_cancelled: true,
+            carry_unfinished: true,
+            reset_recurring_checklists: true,
+            escalate_overdue: true,
+            renumber: true,
+        }
+    }
+}
+
+/// The result of rolling a task set over to a new day, as done
+/// by [`TaskSet::roll_over`].
+///
+/// Displays as a summary of what happened to each task.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rollover {
+    /// The tasks of the new day
+    pub tasks: TaskSet,
+    /// The tasks that were left behind
+    pub dropped: Vec<Task>,
+    /// The tasks of the new day whose priority was raised
+    pub escalated: Vec<TaskId>,
+    /// The new instances of recurring tasks
+    pub scheduled: Vec<TaskId>,
+    /// The tasks that were snoozed until the new day
+    pub woken: Vec<TaskId>,
+}
+
+impl TaskSet {
+    /// Moves the tasks of this set, which belong to the day
+    /// `since`, over to the day of `now` according to `policy`.
+    /// Unfinished tasks count as carried over for every day in
+    /// between.
+    ///
+    /// Recurring tasks that are due for a new instance get one
+    /// first, and new instances are always kept. Tasks count as
+    /// overdue if their deadline passed by `now`.
+    ///
+    /// Snoozed tasks are always kept, but they don't count as
+    /// carried over nor get escalated until they wake up on the
+    /// day they were snoozed until. Only the days since then
+    /// count.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::{NaiveDate, TimeZone}, Priority, RolloverPolicy, Status, Task, TaskSet, Timezone};
+    /// let yesterday = NaiveDate::from_ymd(2023, 1, 1);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Buy milk".into(), Priority::Low, yesterday));
+    /// set.push(Task::new("Walk the dog".into(), Priority::Low, yesterday));
+    /// set.0[0].status = Status::Done;
+    ///
+    /// let now = Timezone::utc().offset().ymd(2023, 1, 2).and_hms(8, 0, 0);
+    /// let rollover = set.roll_over(&RolloverPolicy::default(), yesterday, now);
+    /// assert_eq!(rollover.dropped[0].name, "Buy milk");
+    /// assert_eq!(rollover.tasks.get(1).unwrap().name, "Walk the dog");
+    /// ```
+    pub fn roll_over(
+        mut self,
+        policy: &RolloverPolicy,
+        since: NaiveDate,
+        now: DateTime<FixedOffset>,
+    ) -> Rollover {
+        let today = now.date().naive_local();
+        let days_until_today = |day: NaiveDate| {
+            u32::try_from((today - day).num_days()).unwrap_or(0)
+        };
+        let scheduled: Vec<_> = self
+            .schedule_recurrences(today)
+            .into_iter()
+            .filter_map(|idx| self.get(idx))
+            .map(|task| task.id)
+            .collect();
+
+        // Tasks that wake up today are kept along with the ones
+        // that are still snoozed
+        let is_deferred = |task: &Task| {
+            task.wait_until.is_some() && !task.status.is_closed()
+        };
+        let (mut kept, dropped): (Vec<_>, Vec<_>) =
+            self.0.into_iter().partition(|task| {
+                if scheduled.contains(&task.id)
+                    || is_deferred(task)
+                {
+                    true
+                } else {
+                    match task.status {
+                        Status::Done => !policy.drop_completed,
+                        Status::Cancelled => {
+                            !policy.drop_cancelled
+                        }
+                        _ => policy.carry_unfinished,
+                    }
+                }
+            });
+
+        let mut escalated = Vec::new();
+        let mut woken = Vec::new();
+        for task in kept
+            .iter_mut()
+            .filter(|task| !task.status.is_closed())
+        {
+            if task.is_snoozed(today) {
+                continue;
+            }
+            let awake_since = match task.wait_until.take() {
+                Some(wait_until) => {
+                    woken.push(task.id);
+                    wait_until.max(since)
+                }
+                None => since,
+            };
+
+            if !scheduled.contains(&task.id) {
+                task.carried_over +=
+                    days_until_today(awake_since);
+            }
+
+            if policy.reset_recurring_checklists
+                && task.recurrence.is_some()
+            {
+                task.checklist.reset();
+            }
+
+            let raised = task.priority.raised();
+            if policy.escalate_overdue
+                && task.is_overdue(now)
+                && raised != task.priority
+            {
+                task.priority = raised;
+                escalated.push(task.id);
+            }
+        }
+
+        let mut tasks = TaskSet(kept);
+        if policy.renumber {
+            tasks.renumber();
+        }
+
+        Rollover {
+            tasks,
+            dropped,
+            escalated,
+            scheduled,
+            woken,
+        }
+    }
+}
+
+impl Display for Rollover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let carried = self.tasks.0.len() - self.scheduled.len();
+        write!(f, "Carried over {carried} tasks")?;
+
+        for task in &self.dropped {
+            write!(f, "\n- Dropped '{}' (", task.name)?;
+            if task.status.is_closed() {
+                write!(f, "{})", task.status)?;
+            } else {
+                write!(f, "unfinished)")?;
+            }
+        }
+
+        let tasks_with_ids = |ids: &'_ [TaskId]| {
+            ids.iter()
+                .filter_map(|&id| self.tasks.get_by_id(id))
+                .collect::<Vec<_>>()
+        };
+
+        for task in tasks_with_ids(&self.escalated) {
+            write!(
+                f,
+                "\n↑ Raised priority of overdue '{}' to {}",
+                task.name, task.priority
+            )?;
+        }
+
+        for task in tasks_with_ids(&self.woken) {
+            write!(
+                f,
+                "\n» '{}' is no longer snoozed",
+                task.name
+            )?;
+        }
+
+        for task in tasks_with_ids(&self.scheduled) {
+            write!(f, "\n+ Scheduled '{}'", task.name)?;
+            if let Some(due_date) = task.due_date {
+                write!(f, " for {due_date}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+
+    use super::RolloverPolicy;
+    use crate::{
+        Checkbox, Frequency, Priority, Recurrence, Status, Task,
+        TaskSet, Timezone,
+    };
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2023, 1, day)
+    }
+
+    /// Early in the morning of the given day
+    fn morning(day: u32) -> DateTime<FixedOffset> {
+        Timezone::utc()
+            .offset()
+            .ymd(2023, 1, day)
+            .and_hms(8, 0, 0)
+    }
+
+    fn tasks() -> TaskSet {
+        let mut set = TaskSet(vec![]);
+
+        let mut done =
+            Task::new("Buy milk".into(), Priority::Low, date(1));
+        done.status = Status::Done;
+        set.push(done);
+
+        let mut cancelled = Task::new(
+            "Call the bank".into(),
+            Priority::Low,
+            date(1),
+        );
+        cancelled.status = Status::Cancelled;
+        set.push(cancelled);
+
+        let mut overdue =
+            Task::new("Pay rent".into(), Priority::Low, date(1));
+        overdue.due_date = Some(date(1));
+        set.push(overdue);
+
+        let mut recurring = Task::new(
+            "Water the plants".into(),
+            Priority::Medium,
+            date(2),
+        );
+        recurring.recurrence =
+            Some(Recurrence::new(Frequency::EveryNDays(3)));
+        recurring.checklist =
+            vec![Checkbox::with_description("Basil".into())
+                .with_status(true)]
+            .into_iter()
+            .collect();
+        set.push(recurring);
+
+        set
+    }
+
+    #[test]
+    fn follows_the_default_policy() {
+        let rollover = tasks().roll_over(
+            &RolloverPolicy::default(),
+            date(1),
+            morning(2),
+        );
+
+        let names: Vec<_> = rollover
+            .tasks
+            .0
+            .iter()
+            .map(|task| task.name.as_str())
+            .collect();
+        assert_eq!(names, ["Pay rent", "Water the plants"]);
+        assert_eq!(rollover.tasks.0[0].idx, 1);
+
+        assert_eq!(
+            rollover.tasks.0[0].priority,
+            Priority::Medium
+        );
+        assert_eq!(rollover.escalated, [rollover.tasks.0[0].id]);
+        assert!(!rollover.tasks.0[1].checklist[0].is_done());
+        assert_eq!(rollover.tasks.0[1].carried_over, 1);
+
+        assert_eq!(
+            rollover.to_string(),
+            "Carried over 2 tasks\n\
+             - Dropped 'Buy milk' (done)\n\
+             - Dropped 'Call the bank' (cancelled)\n\
+             ↑ Raised priority of overdue 'Pay rent' to MEDIUM"
+        );
+    }
+
+    #[test]
+    fn follows_custom_policies() {
+        let policy = RolloverPolicy {
+            drop_completed: false,
+            drop_cancelled: true,
+            carry_unfinished: false,
+            reset_recurring_checklists: false,
+            escalate_overdue: false,
+            renumber: false,
+        };
+
+        // The recurring task is due for a new instance by now
+        let rollover =
+            tasks().roll_over(&policy, date(1), morning(5));
+
+        let names: Vec<_> = rollover
+            .tasks
+            .0
+            .iter()
+            .map(|task| task.name.as_str())
+            .collect();
+        assert_eq!(names, ["Buy milk", "Water the plants"]);
+        assert_eq!(rollover.tasks.0[1].idx, 5);
+        assert_eq!(rollover.tasks.0[1].due_date, Some(date(5)));
+        assert_eq!(rollover.tasks.0[1].carried_over, 0);
+        assert_eq!(rollover.dropped.len(), 3);
+        assert!(rollover.escalated.is_empty());
+    }
+
+    #[test]
+    fn keeps_snoozed_tasks_until_they_wake_up() {
+        let policy = RolloverPolicy {
+            carry_unfinished: false,
+            ..RolloverPolicy::default()
+        };
+        let mut set = tasks();
+        set.0[2].wait_until = Some(date(3));
+        let id = set.0[2].id;
+
+        let rollover =
+            set.roll_over(&policy, date(1), morning(2));
+        let snoozed = rollover.tasks.get_by_id(id).unwrap();
+        assert_eq!(snoozed.carried_over, 0);
+        assert_eq!(snoozed.priority, Priority::Low);
+        assert!(rollover.woken.is_empty());
+
+        // Only the days after waking up count
+        let rollover = rollover.tasks.roll_over(
+            &policy,
+            date(2),
+            morning(5),
+        );
+        assert_eq!(rollover.woken, [id]);
+        assert_eq!(rollover.tasks.0[0].carried_over, 2);
+        assert_eq!(rollover.tasks.0[0].wait_until, None);
+        assert_eq!(
+            rollover.tasks.0[0].priority,
+            Priority::Medium
+        );
+        assert!(rollover
+            .to_string()
+            .contains("» 'Pay rent' is no longer snoozed"));
+    }
+
+    #[test]
+    fn counts_every_day_in_between() {
+        let rollover = tasks().roll_over(
+            &RolloverPolicy::default(),
+            date(2),
+            morning(9),
+        );
+
+        assert_eq!(rollover.tasks.0[0].name, "Pay rent");
+        assert_eq!(rollover.tasks.0[0].carried_over, 7);
+
+        let rollover = rollover.tasks.roll_over(
+            &RolloverPolicy::default(),
+            date(9),
+            morning(10),
+        );
+        assert_eq!(rollover.tasks.0[0].carried_over, 8);
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Where a task is in its lifecycle.
+///
+/// In the text representation of tasks, each status is written
+/// as a [mark](Status::mark) between brackets.
+///
+/// ```rust
+/// # use dodo_internals::Status;
+/// assert_eq!(Status::from_mark('~'), Some(Status::InProgress));
+/// assert_eq!(Status::Waiting.mark(), '?');
+/// assert!(Status::Cancelled.is_closed());
+/// assert!(!Status::Blocked.is_closed());
+/// ```
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Default,
+)]
+pub enum Status {
+    /// Not started yet
+    #[default]
+    Todo,
+    /// Being worked on
+    InProgress,
+    /// Can't move forward until something else is done
+    Blocked,
+    /// Waiting on someone or something outside of our control
+    Waiting,
+    /// Won't be done
+    Cancelled,
+    /// Finished
+    Done,
+}
+
+impl Status {
+    /// Every status, in the order they're usually gone through.
+    pub const ALL: [Status; 6] = [
+        Status::Todo,
+        Status::InProgress,
+        Status::Blocked,
+        Status::Waiting,
+        Status::Cancelled,
+        Status::Done,
+    ];
+
+    /// The character written between brackets for this status.
+    pub fn mark(self) -> char {
+        match self {
+            Status::Todo => ' ',
+            Status::InProgress => '~',
+            Status::Blocked => '!',
+            Status::Waiting => '?',
+            Status::Cancelled => '-',
+            Status::Done => 'x',
+        }
+    }
+
+    /// The status written as the given mark, if there's any.
+    /// Both 'x' and 'X' mean [`Status::Done`].
+    pub fn from_mark(mark: char) -> Option<Self> {
+        match mark {
+            'X' => Some(Status::Done),
+            mark => Self::ALL
+                .into_iter()
+                .find(|status| status.mark() == mark),
+        }
+    }
+
+    /// Returns true if no more work is going into tasks with
+    /// this status, whether they were finished or not.
+    pub fn is_closed(self) -> bool {
+        matches!(self, Status::Done | Status::Cancelled)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Status::Todo => "to do",
+            Status::InProgress => "in progress",
+            Status::Blocked => "blocked",
+            Status::Waiting => "waiting",
+            Status::Cancelled => "cancelled",
+            Status::Done => "done",
+        }
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Accepts the names of the statuses, with dashes, underscores
+/// or nothing in place of spaces, such as "in-progress" or
+/// "todo".
+impl FromStr for Status {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|ch| !matches!(ch, ' ' | '-' | '_'))
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let input = normalize(input);
+
+        match input.as_str() {
+            "canceled" => Ok(Status::Cancelled),
+            _ => Self::ALL
+                .into_iter()
+                .find(|status| normalize(status.name()) == input)
+                .ok_or(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+
+    #[test]
+    fn marks_and_names_round_trip() {
+        for status in Status::ALL {
+            assert_eq!(
+                Status::from_mark(status.mark()),
+                Some(status)
+            );
+            assert_eq!(status.to_string().parse(), Ok(status));
+        }
+
+        assert_eq!(
+            "in-progress".parse(),
+            Ok(Status::InProgress)
+        );
+        assert_eq!("TODO".parse(), Ok(Status::Todo));
+        assert_eq!("canceled".parse(), Ok(Status::Cancelled));
+        assert_eq!("finished".parse::<Status>(), Err(()));
+        assert_eq!(Status::from_mark('*'), None);
+    }
+}
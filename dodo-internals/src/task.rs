@@ -1,19 +1,69 @@
-use std::fmt::{self, Display};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+};
 
-use chrono::NaiveDate;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate};
 use serde::{Deserialize, Serialize};
 
-use crate::{Checklist, Priority};
+use crate::{
+    utils::{escape, format_duration},
+    AttributeValue, Checklist, DueTime, Priority, Recurrence,
+    Status, TaskId, TimeEntry, Timezone,
+};
 
 impl Display for Task {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let is_done = if self.is_done { "x" } else { " " };
-        let name = &self.name;
+        let mark = self.status.mark();
+        let name = escape(&self.name);
         let priority = self.priority;
         let checklist = &self.checklist;
 
-        writeln!(f, "[{is_done}] {name} [{priority}]")?;
-        writeln!(f, "{checklist}")
+        write!(f, "[{mark}] {name} [{priority}]")?;
+        if let Some(project) = &self.project {
+            write!(f, " +{project}")?;
+        }
+        if let Some(context) = &self.context {
+            write!(f, " @{context}")?;
+        }
+        for tag in &self.tags {
+            write!(f, " #{tag}")?;
+        }
+        if let Some(due_date) = self.due_date {
+            write!(f, " due:{due_date}")?;
+            if let Some(due_time) = self.due_time {
+                write!(f, "T{due_time}")?;
+            }
+        }
+        if let Some(wait_until) = self.wait_until {
+            write!(f, " wait:{wait_until}")?;
+        }
+        if let Some(recurrence) = &self.recurrence {
+            write!(f, " {recurrence}")?;
+        }
+        if let Some(estimate) = self.estimate {
+            write!(f, " est:{}", format_duration(estimate))?;
+        }
+        for (key, value) in &self.attributes {
+            write!(f, " {key}:{value}")?;
+        }
+        for dependency in &self.depends_on {
+            write!(f, " after:{dependency}")?;
+        }
+        write!(f, " id:{}", self.id)?;
+        writeln!(f)?;
+        write!(f, "{checklist}")?;
+        if !self.notes.is_empty() {
+            for line in self.notes.split('\n') {
+                match line {
+                    "" => writeln!(f, "    >")?,
+                    line => writeln!(f, "    > {line}")?,
+                }
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -24,66 +74,336 @@ impl Display for Task {
 pub struct Task {
     /// The index of this task
     pub idx: usize,
+    /// This task's unique identifier, which never changes
+    pub id: TaskId,
     /// This task's name
     pub name: String,
-    /// Whether or not this task is done
-    pub is_done: bool,
+    /// Where this task is in its lifecycle
+    pub status: Status,
+    /// When this task was marked as done, if it's done
+    pub completed_at: Option<DateTime<FixedOffset>>,
+    /// When this task was cancelled, if it's cancelled
+    pub cancelled_at: Option<DateTime<FixedOffset>>,
     /// When this task was created
     pub creation_date: NaiveDate,
+    /// How many days this task was moved over to a later day
+    /// without being done, across all of its rollovers
+    pub carried_over: u32,
     /// This tasks's due date, if any
     pub due_date: Option<NaiveDate>,
+    /// The time of day this task is due at, if any. Only taken
+    /// into account along with a due date
+    pub due_time: Option<DueTime>,
+    /// The day this task is snoozed until, if it's snoozed. It
+    /// stays out of the list until then
+    pub wait_until: Option<NaiveDate>,
+    /// How often this task comes back, if it does
+    pub recurrence: Option<Recurrence>,
+    /// The tasks that must be done before this one can start
+    pub depends_on: Vec<TaskId>,
     /// This task's overall priority
     pub priority: Priority,
+    /// Free-form labels used to categorize this task
+    pub tags: BTreeSet<String>,
+    /// The project this task is a part of, if any
+    pub project: Option<String>,
+    /// Where this task can be done (such as "home" or
+    /// "office"), if anywhere in particular
+    pub context: Option<String>,
     /// This task's checklist
     pub checklist: Checklist,
+    /// Free-form notes about this task, such as links or
+    /// acceptance criteria. Empty if there are none
+    pub notes: String,
+    /// How long this task is expected to take, if known
+    #[serde(with = "crate::time_log::optional_seconds")]
+    pub estimate: Option<Duration>,
+    /// The stretches of time spent working on this task
+    pub time_log: Vec<TimeEntry>,
+    /// Custom attributes, such as "ticket:ENG-123", as declared
+    /// by an [`AttributeSchema`](crate::AttributeSchema)
+    pub attributes: BTreeMap<String, AttributeValue>,
 }
 
-impl PartialOrd for Task {
-    fn partial_cmp(
+impl Task {
+    /// Creates a pending task with no due date and an empty
+    /// checklist.
+    ///
+    /// Its index is set once it gets added to a
+    /// [`TaskSet`](crate::TaskSet).
+    pub fn new(
+        name: String,
+        priority: Priority,
+        creation_date: NaiveDate,
+    ) -> Self {
+        Self {
+            idx: 0,
+            id: TaskId::generate(),
+            name,
+            status: Status::Todo,
+            completed_at: None,
+            cancelled_at: None,
+            creation_date,
+            carried_over: 0,
+            due_date: None,
+            due_time: None,
+            wait_until: None,
+            recurrence: None,
+            depends_on: vec![],
+            priority,
+            tags: BTreeSet::new(),
+            project: None,
+            context: None,
+            checklist: Checklist::with_checkboxes(vec![]),
+            notes: String::new(),
+            estimate: None,
+            time_log: vec![],
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Copies over the fields of `previous` that aren't part of
+    /// the text representation of a task.
+    pub(crate) fn restore_hidden_fields(
+        &mut self,
+        previous: &Task,
+    ) {
+        self.creation_date = previous.creation_date;
+        self.carried_over = previous.carried_over;
+
+        if self.status == previous.status {
+            self.completed_at = previous.completed_at;
+            self.cancelled_at = previous.cancelled_at;
+        }
+
+        // Attributes that are no longer declared are read back as
+        // strings, so the ones that weren't edited keep their type
+        for (key, value) in &mut self.attributes {
+            if let (AttributeValue::String(text), Some(old)) =
+                (&*value, previous.attributes.get(key))
+            {
+                if old.to_string() == *text {
+                    *value = old.clone();
+                }
+            }
+        }
+
+        self.time_log = previous.time_log.clone();
+        if let Some(finished_at) =
+            self.completed_at.or(self.cancelled_at)
+        {
+            self.stop_timer(finished_at);
+        }
+    }
+
+    /// Returns true if this task was snoozed until a day after
+    /// `today`.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Priority, Task};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut task = Task::new("Renew passport".into(), Priority::Low, today);
+    /// task.wait_until = Some(today.succ());
+    ///
+    /// assert!(task.is_snoozed(today));
+    /// assert!(!task.is_snoozed(today.succ()));
+    /// ```
+    pub fn is_snoozed(&self, today: NaiveDate) -> bool {
+        self.wait_until.is_some_and(|date| date > today)
+    }
+
+    /// Returns true if this task is [done](Status::Done).
+    pub fn is_done(&self) -> bool {
+        self.status == Status::Done
+    }
+
+    /// Returns true if this task is still open and either its
+    /// [deadline](Task::deadline) has passed by `now` or one of
+    /// its unchecked checkboxes was due before today.
+    ///
+    /// Due times that aren't in a specific timezone are taken to
+    /// be in the timezone of `now`.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::{NaiveDate, TimeZone}, Checkbox, Priority, Task, Timezone};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut task = Task::new("Plan the trip".into(), Priority::Low, today);
+    /// task.due_date = Some(today);
+    /// task.due_time = Some("15:00".parse().unwrap());
+    ///
+    /// let offset = Timezone::utc().offset();
+    /// assert!(!task.is_overdue(offset.ymd(2023, 1, 2).and_hms(14, 0, 0)));
+    /// assert!(task.is_overdue(offset.ymd(2023, 1, 2).and_hms(16, 0, 0)));
+    ///
+    /// task.due_date = None;
+    /// let flights = Checkbox::with_description("Book flights".into())
+    ///     .with_due_date(today.pred());
+    /// task.checklist.push(flights);
+    /// assert!(task.is_overdue(offset.ymd(2023, 1, 2).and_hms(0, 0, 0)));
+    /// ```
+    pub fn is_overdue(
         &self,
-        other: &Self,
-    ) -> Option<std::cmp::Ordering> {
-        let both_have_due_dates =
-            self.due_date.is_some() && other.due_date.is_some();
-        // If any of the tasks have no due date, then the
-        // comparison is based on priority only
-        if !both_have_due_dates
-            || self.priority != other.priority
+        now: DateTime<FixedOffset>,
+    ) -> bool {
+        let timezone = Timezone::from(*now.offset());
+        let today = now.date().naive_local();
+
+        !self.status.is_closed()
+            && (self
+                .deadline(timezone)
+                .is_some_and(|deadline| deadline < now)
+                || !self.checklist.overdue(today).is_empty())
+    }
+
+    /// Changes the status of this task, recording when it was
+    /// completed or cancelled if that's what happened. Finishing
+    /// a task stops its timer.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::{NaiveDate, TimeZone}, Priority, Status, Task, Timezone};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut task = Task::new("Buy milk".into(), Priority::Low, today);
+    /// let now = Timezone::utc().offset().ymd(2023, 1, 2).and_hms(18, 0, 0);
+    ///
+    /// task.set_status(Status::Done, now);
+    /// assert_eq!(task.completed_at, Some(now));
+    ///
+    /// task.set_status(Status::Blocked, now);
+    /// assert_eq!(task.completed_at, None);
+    /// ```
+    pub fn set_status(
+        &mut self,
+        status: Status,
+        now: DateTime<FixedOffset>,
+    ) {
+        if self.status == status {
+            return;
+        }
+
+        self.status = status;
+        self.completed_at =
+            (status == Status::Done).then_some(now);
+        self.cancelled_at =
+            (status == Status::Cancelled).then_some(now);
+        if status.is_closed() {
+            self.stop_timer(now);
+        }
+    }
+
+    /// The date this task next comes back on, counting from its
+    /// due date or, lacking one, from its creation date.
+    pub fn next_occurrence(&self) -> Option<NaiveDate> {
+        let since = self.due_date.unwrap_or(self.creation_date);
+
+        self.recurrence.as_ref()?.next_after(since)
+    }
+
+    /// Creates the next instance of this task, if it recurs, and
+    /// hands the recurrence over to it so that the same instance
+    /// is never created twice.
+    ///
+    /// The new instance is due on the latest occurrence that
+    /// already arrived by `today`, or on the upcoming one if
+    /// there's none, so missed occurrences don't pile up. An
+    /// instance that isn't due yet is snoozed until it is.
+    pub fn recur(&mut self, today: NaiveDate) -> Option<Task> {
+        let recurrence = self.recurrence.as_ref()?;
+
+        let mut due_date = self.next_occurrence()?;
+        while let Some(next) = recurrence
+            .next_after(due_date)
+            .filter(|next| *next <= today)
         {
-            return self.priority.partial_cmp(&other.priority);
+            due_date = next;
         }
 
-        // We now know that both tasks have the same priority so
-        // the comparison will be based on the due date
-        // We also know that both tasks have a due date, so the
-        // unwraps are safe.
-        let self_due_date = &self.due_date.unwrap();
-        let other_due_date = &other.due_date.unwrap();
+        let mut checklist = self.checklist.clone();
+        checklist.reset();
 
-        Some(other_due_date.cmp(self_due_date))
+        Some(Task {
+            idx: 0,
+            id: TaskId::generate(),
+            status: Status::Todo,
+            completed_at: None,
+            cancelled_at: None,
+            creation_date: today,
+            carried_over: 0,
+            due_date: Some(due_date),
+            wait_until: (due_date > today).then_some(due_date),
+            recurrence: self.recurrence.take(),
+            checklist,
+            time_log: vec![],
+            ..self.clone()
+        })
+    }
+
+    /// Compares how pressing two tasks are, the more pressing
+    /// one being greater: higher priorities come first, then
+    /// sooner deadlines (which account for the time of day
+    /// tasks are due at), then tasks with a deadline over the
+    /// ones without, then older tasks.
+    ///
+    /// Tasks that tie on all of that are told apart by their
+    /// ID, which makes this a total order of distinct tasks.
+    /// Two versions of the same task are only equal as far as
+    /// this goes, which is why it isn't `Task`'s `Ord`.
+    pub fn cmp_pressing(&self, other: &Task) -> Ordering {
+        // Sooner deadlines are greater, and any deadline is
+        // greater than none
+        let deadline = |task: &Task| {
+            task.deadline(Timezone::utc()).map(Reverse)
+        };
+
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| deadline(self).cmp(&deadline(other)))
+            .then_with(|| {
+                other.creation_date.cmp(&self.creation_date)
+            })
+            .then_with(|| self.id.cmp(&other.id))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use chrono::Duration;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use chrono::{Duration, NaiveDate};
 
     use super::{Priority, Task};
-    use crate::{utils::today, Checkbox};
+    use crate::{
+        test_utils::today, Checkbox, Frequency, Recurrence,
+        Status, TaskId,
+    };
 
     fn dummy_task() -> Task {
         Task {
             idx: 1,
+            id: TaskId::generate(),
             name: "Dummy".into(),
-            is_done: false,
+            status: Status::Todo,
+            completed_at: None,
+            cancelled_at: None,
             creation_date: today(),
+            carried_over: 0,
             due_date: None,
+            due_time: None,
+            wait_until: None,
+            recurrence: None,
+            depends_on: vec![],
             priority: Priority::Low,
+            tags: BTreeSet::new(),
+            project: None,
+            context: None,
             checklist: vec![Checkbox::with_description(
                 "Procurar metodologia".into(),
             )]
             .into_iter()
             .collect(),
+            notes: String::new(),
+            estimate: None,
+            time_log: vec![],
+            attributes: BTreeMap::new(),
         }
     }
 
@@ -102,11 +422,11 @@ mod tests {
 
         task1.priority = Priority::Medium;
 
-        assert!(task1 > task2);
+        assert!(task1.cmp_pressing(&task2).is_gt());
 
         task2.priority = Priority::High;
 
-        assert!(task2 > task1);
+        assert!(task2.cmp_pressing(&task1).is_gt());
     }
 
     #[test]
@@ -128,12 +448,69 @@ mod tests {
 
         // Task no. 1 has the priority since it's closer to its
         // due date than task no. 2
-        assert!(task1 > task2);
+        assert!(task1.cmp_pressing(&task2).is_gt());
 
         task2.due_date = Some(today);
 
         // Task no. 2 now has the priority since it's closer to
         // its due date than task no. 1
-        assert!(task1 < task2);
+        assert!(task1.cmp_pressing(&task2).is_lt());
+    }
+
+    #[test]
+    fn pressing_order_is_total() {
+        let mut due = dummy_task();
+        due.due_date = Some(today());
+        let mut old = dummy_task();
+        old.creation_date = today() - Duration::days(3);
+        let new = dummy_task();
+
+        // Any deadline beats none, and older tasks beat newer ones
+        assert!(due.cmp_pressing(&old).is_gt());
+        assert!(old.cmp_pressing(&new).is_gt());
+        assert!(new.cmp_pressing(&new.clone()).is_eq());
+
+        let mut tasks =
+            vec![new.clone(), due.clone(), old.clone()];
+        tasks.sort_by(Task::cmp_pressing);
+        assert_eq!(tasks, [new, old, due]);
+    }
+
+    #[test]
+    fn recurring_tasks_skip_missed_occurrences() {
+        let mut task = dummy_task();
+        task.checklist.get_mut(0).unwrap().toggle();
+        task.recurrence =
+            Some(Recurrence::new(Frequency::Daily));
+        task.due_date = Some(NaiveDate::from_ymd(2023, 1, 2));
+        task.wait_until = Some(NaiveDate::from_ymd(2023, 1, 20));
+
+        let today = NaiveDate::from_ymd(2023, 1, 10);
+        let next = task.recur(today).unwrap();
+
+        assert_eq!(next.due_date, Some(today));
+        assert_eq!(next.wait_until, None);
+        assert_eq!(next.creation_date, today);
+        assert!(!next.checklist[0].is_done());
+        assert_ne!(next.id, task.id);
+
+        // The recurrence now belongs to the new instance
+        assert!(next.recurrence.is_some());
+        assert!(task.recur(today).is_none());
+    }
+
+    #[test]
+    fn recurring_tasks_sleep_until_they_are_due() {
+        let mut task = dummy_task();
+        task.recurrence =
+            Some(Recurrence::new(Frequency::Daily));
+        task.due_date = Some(today());
+
+        let next = task.recur(today()).unwrap();
+
+        assert_eq!(next.due_date, Some(today().succ()));
+        assert_eq!(next.wait_until, Some(today().succ()));
+        assert!(next.is_snoozed(today()));
+        assert!(!next.is_snoozed(today().succ()));
     }
 }
@@ -0,0 +1,134 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt::{self, Display},
+    hash::{BuildHasher, Hasher},
+    num::ParseIntError,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Uniquely identifies a task, regardless of its index or of
+/// the day it belongs to.
+///
+/// ```rust
+/// # use dodo_internals::TaskId;
+/// let id = TaskId::generate();
+/// assert_eq!(id.to_string().len(), 16);
+/// assert_eq!(id.to_string().parse(), Ok(id));
+/// assert!(id.to_string().starts_with(&id.short()));
+/// assert!(id.starts_with(&id.short()));
+/// ```
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Copy,
+)]
+pub struct TaskId(u64);
+
+impl TaskId {
+    /// The amount of hex digits shown by [`TaskId::short`].
+    pub const SHORT_LEN: usize = 7;
+
+    /// Generates a new random identifier.
+    pub fn generate() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos());
+
+        // RandomState is seeded with random keys, which makes
+        // this hasher a good enough source of randomness
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        hasher
+            .write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+
+        Self(hasher.finish())
+    }
+
+    /// Derives an identifier from `parts`, which is the same
+    /// every time for the same parts, no matter the version of
+    /// Rust or of any dependency.
+    pub(crate) fn derive(parts: &[&str]) -> Self {
+        // 64-bit FNV-1a, with a byte that never shows up in
+        // UTF-8 between the parts
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+        const SEPARATOR: u8 = 0xff;
+
+        let mut bytes = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 {
+                bytes.push(SEPARATOR);
+            }
+            bytes.extend_from_slice(part.as_bytes());
+        }
+
+        let hash =
+            bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+            });
+
+        Self(hash)
+    }
+
+    /// The first few hex digits of this identifier, which are
+    /// usually enough to tell tasks apart.
+    pub fn short(&self) -> String {
+        let mut short = self.to_string();
+        short.truncate(Self::SHORT_LEN);
+
+        short
+    }
+
+    /// Returns true if the hex representation of this
+    /// identifier starts with `prefix`.
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        !prefix.is_empty()
+            && self
+                .to_string()
+                .starts_with(&prefix.to_ascii_lowercase())
+    }
+}
+
+impl Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for TaskId {
+    type Err = ParseIntError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(input, 16).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TaskId;
+
+    #[test]
+    fn derives_the_same_id_everywhere() {
+        let id =
+            TaskId::derive(&["2023-01-02", "Fill out my tasks"]);
+
+        assert_eq!(id.to_string(), "ba743c722470b58d");
+        assert_ne!(
+            id,
+            TaskId::derive(&["2023-01-02Fill out my tasks"])
+        );
+    }
+}
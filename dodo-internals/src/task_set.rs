@@ -1,8 +1,13 @@
-use std::fmt::{self, Display};
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, HashMap},
+    fmt::{self, Display},
+};
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-use crate::Task;
+use crate::{Task, TaskId};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[repr(transparent)]
@@ -19,19 +24,268 @@ impl Display for TaskSet {
 }
 
 impl TaskSet {
-    /// Returns the first incorrect index (starting at 1) in this
-    /// taskset, if there's any
-    pub fn check_for_invalid_indices(&self) -> Option<usize> {
-        for (idx, task) in self.0.iter().enumerate() {
-            let expected_idx = idx + 1;
-            if expected_idx != task.idx {
-                eprintln!("Error: Expected index {expected_idx}, received {t}", t=task.idx);
-                return Some(expected_idx);
+    /// Returns a reference to the task with the given index, if
+    /// there's any.
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Priority, Task, TaskSet};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Buy milk".into(), Priority::Low, today));
+    /// assert_eq!(set.get(1).unwrap().name, "Buy milk");
+    /// assert!(set.get(2).is_none());
+    /// ```
+    pub fn get(&self, idx: usize) -> Option<&Task> {
+        self.0.iter().find(|task| task.idx == idx)
+    }
+
+    /// Returns a reference to the task with the given ID, if
+    /// there's any.
+    pub fn get_by_id(&self, id: TaskId) -> Option<&Task> {
+        self.0.iter().find(|task| task.id == id)
+    }
+
+    /// Returns the tasks whose IDs start with the given prefix
+    /// of hex digits.
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Priority, Task, TaskSet};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Buy milk".into(), Priority::Low, today));
+    ///
+    /// let prefix = set.0[0].id.short();
+    /// let found: Vec<_> = set.with_id_prefix(&prefix).collect();
+    /// assert_eq!(found, [&set.0[0]]);
+    /// ```
+    pub fn with_id_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = &'a Task> {
+        self.0
+            .iter()
+            .filter(move |task| task.id.starts_with(prefix))
+    }
+
+    /// Returns a mutable reference to the task with the given
+    /// index, if there's any.
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut Task> {
+        self.0.iter_mut().find(|task| task.idx == idx)
+    }
+
+    /// Appends a task to this set, giving it the next available
+    /// index. Returns the index it was given.
+    pub fn push(&mut self, mut task: Task) -> usize {
+        let idx = self.0.len() + 1;
+        task.idx = idx;
+        self.0.push(task);
+
+        idx
+    }
+
+    /// Removes the task with the given index and renumbers the
+    /// tasks after it so that indices stay contiguous.
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Priority, Task, TaskSet};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Buy milk".into(), Priority::Low, today));
+    /// set.push(Task::new("Walk the dog".into(), Priority::Low, today));
+    ///
+    /// let removed = set.remove(1).unwrap();
+    /// assert_eq!(removed.name, "Buy milk");
+    /// assert_eq!(set.get(1).unwrap().name, "Walk the dog");
+    /// ```
+    pub fn remove(&mut self, idx: usize) -> Option<Task> {
+        let position =
+            self.0.iter().position(|task| task.idx == idx)?;
+        let removed = self.0.remove(position);
+
+        self.renumber();
+
+        Some(removed)
+    }
+
+    /// Sets the index of every task to its position (starting
+    /// at 1) in this set.
+    pub fn renumber(&mut self) {
+        for (position, task) in self.0.iter_mut().enumerate() {
+            task.idx = position + 1;
+        }
+    }
+
+    /// Creates the next instance of every recurring task that
+    /// was completed or whose next occurrence arrived by
+    /// `today`, as described in [`Task::recur`]. Returns the
+    /// indices of the new instances.
+    ///
+    /// Cancelling a recurring task stops it from coming back.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Priority, Status, Task, TaskSet};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut task = Task::new("Water the plants".into(), Priority::Low, today);
+    /// task.recurrence = Some("3days".parse().unwrap());
+    ///
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(task);
+    /// assert!(set.schedule_recurrences(today).is_empty());
+    ///
+    /// set.0[0].status = Status::Done;
+    /// assert_eq!(set.schedule_recurrences(today), [2]);
+    /// assert_eq!(set.0[1].due_date, Some(NaiveDate::from_ymd(2023, 1, 5)));
+    /// assert!(set.schedule_recurrences(today).is_empty());
+    /// ```
+    pub fn schedule_recurrences(
+        &mut self,
+        today: NaiveDate,
+    ) -> Vec<usize> {
+        let instances: Vec<_> = self
+            .0
+            .iter_mut()
+            .filter(|task| {
+                let has_come_back = task
+                    .next_occurrence()
+                    .is_some_and(|next| next <= today);
+
+                task.is_done()
+                    || (!task.status.is_closed()
+                        && has_come_back)
+            })
+            .filter_map(|task| task.recur(today))
+            .collect();
+
+        instances
+            .into_iter()
+            .map(|task| self.push(task))
+            .collect()
+    }
+
+    /// Returns the open tasks that were carried over for more
+    /// than `days` days, starting with the ones carried over
+    /// the most.
+    pub fn stale(&self, days: u32) -> Vec<&Task> {
+        let mut stale: Vec<_> = self
+            .0
+            .iter()
+            .filter(|task| {
+                !task.status.is_closed()
+                    && task.carried_over > days
+            })
+            .collect();
+        stale.sort_by_key(|task| Reverse(task.carried_over));
+
+        stale
+    }
+
+    /// Pairs up each task of `newer` with the position of the
+    /// task in this set that it most likely corresponds to, if
+    /// there's any.
+    ///
+    /// Tasks are first matched by ID. Tasks whose ID was lost
+    /// (such as when the user deletes it from the text) are then
+    /// matched by name, so that reordering or renumbering them
+    /// doesn't change what they correspond to, and lastly by
+    /// index, so that renamed tasks are still found.
+    pub(crate) fn correspondences(
+        &self,
+        newer: &TaskSet,
+    ) -> Vec<Option<usize>> {
+        let mut claimed = vec![false; self.0.len()];
+        let mut matches = vec![None; newer.0.len()];
+
+        let passes: [fn(&Task, &Task) -> bool; 3] = [
+            |old, new| old.id == new.id,
+            |old, new| old.name == new.name,
+            |old, new| old.idx == new.idx,
+        ];
+
+        for is_match in passes {
+            for (new, matched) in
+                newer.0.iter().zip(&mut matches)
+            {
+                if matched.is_some() {
+                    continue;
+                }
+
+                let position =
+                    self.0.iter().enumerate().position(
+                        |(pos, old)| {
+                            !claimed[pos] && is_match(old, new)
+                        },
+                    );
+
+                if let Some(pos) = position {
+                    claimed[pos] = true;
+                    *matched = Some(pos);
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// Restores the information that isn't part of the text
+    /// representation of tasks (such as their creation date)
+    /// from the tasks of `previous` they correspond to.
+    ///
+    /// Meant to be used on task sets parsed from text, which
+    /// would otherwise lose that information. Tasks that lost
+    /// their ID get it back, and tasks sharing an ID (such as
+    /// copy-pasted ones) get new ones.
+    pub fn reconcile(&mut self, previous: &TaskSet) {
+        let matches = previous.correspondences(self);
+
+        for (pos, matched) in matches.into_iter().enumerate() {
+            let previous = match matched {
+                Some(matched) => &previous.0[matched],
+                None => continue,
+            };
+
+            let id_is_free =
+                self.0.iter().all(|task| task.id != previous.id);
+            let task = &mut self.0[pos];
+            if id_is_free {
+                task.id = previous.id;
             }
+
+            task.restore_hidden_fields(previous);
         }
 
-        None
+        for pos in 1..self.0.len() {
+            let (before, after) = self.0.split_at_mut(pos);
+            let task = &mut after[0];
+
+            if before.iter().any(|other| other.id == task.id) {
+                task.id = TaskId::generate();
+            }
+        }
+    }
+}
+
+/// The latest version of each task in `history`, which goes from
+/// the oldest to the newest day, along with the day it's from.
+///
+/// Tasks are listed in the order they first showed up in.
+pub(crate) fn latest_versions<'a>(
+    history: impl IntoIterator<Item = (NaiveDate, &'a TaskSet)>,
+) -> Vec<(NaiveDate, &'a Task)> {
+    let mut positions = HashMap::new();
+    let mut latest = Vec::new();
+
+    for (date, set) in history {
+        for task in &set.0 {
+            match positions.entry(task.id) {
+                Entry::Occupied(entry) => {
+                    latest[*entry.get()] = (date, task);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(latest.len());
+                    latest.push((date, task));
+                }
+            }
+        }
     }
+
+    latest
 }
 
 impl AsRef<[Task]> for TaskSet {
@@ -39,3 +293,116 @@ impl AsRef<[Task]> for TaskSet {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{
+        test_utils::today, AttributeValue, Priority, Status,
+        Task, TaskId, TaskSet,
+    };
+
+    fn task(name: &str, creation_date: NaiveDate) -> Task {
+        Task::new(name.into(), Priority::Low, creation_date)
+    }
+
+    #[test]
+    fn reconcile_keeps_creation_dates() {
+        let long_ago = NaiveDate::from_ymd(2022, 6, 30);
+        let yesterday = today().pred();
+
+        let mut stored = TaskSet(vec![]);
+        stored.push(task("Write report", long_ago));
+        stored.push(task("Buy milk", yesterday));
+        stored.push(task("Walk the dog", yesterday));
+
+        // The first task was removed, the other two were
+        // swapped, one of them renamed and a new one added
+        let mut parsed = TaskSet(vec![]);
+        parsed.push(task("Walk the dog", today()));
+        parsed.push(task("Buy oat milk", today()));
+        parsed.push(task("Call mom", today()));
+
+        parsed.reconcile(&stored);
+
+        let dates: Vec<_> = parsed
+            .0
+            .iter()
+            .map(|task| task.creation_date)
+            .collect();
+        assert_eq!(dates, [yesterday, yesterday, today()]);
+    }
+
+    #[test]
+    fn finds_stale_tasks() {
+        let mut set = TaskSet(vec![]);
+        for (name, carried_over) in [
+            ("Write report", 2),
+            ("Buy milk", 0),
+            ("Call mom", 5),
+        ] {
+            let mut task = task(name, today());
+            task.carried_over = carried_over;
+            set.push(task);
+        }
+
+        let stale: Vec<_> = set
+            .stale(1)
+            .into_iter()
+            .map(|task| task.idx)
+            .collect();
+        assert_eq!(stale, [3, 1]);
+
+        set.0[2].status = Status::Cancelled;
+        assert_eq!(set.stale(1).len(), 1);
+    }
+
+    #[test]
+    fn reconcile_keeps_ids_unique() {
+        let mut stored = TaskSet(vec![]);
+        stored.push(task("Write report", today()));
+        stored.push(task("Buy milk", today()));
+
+        // The second task was duplicated and the ID of the first
+        // one was deleted from the text
+        let mut parsed = stored.clone();
+        parsed.push(stored.0[1].clone());
+        parsed.0[0].id = TaskId::generate();
+
+        parsed.reconcile(&stored);
+
+        assert_eq!(parsed.0[0].id, stored.0[0].id);
+        assert_eq!(parsed.0[1].id, stored.0[1].id);
+        assert_ne!(parsed.0[2].id, stored.0[1].id);
+    }
+
+    #[test]
+    fn reconcile_keeps_the_type_of_undeclared_attributes() {
+        let mut stored = TaskSet(vec![]);
+        stored.push(task("Fix login", today()));
+        stored.0[0].attributes.insert(
+            "points".into(),
+            AttributeValue::Number(3.0),
+        );
+
+        // "points" was no longer declared when the text was read
+        let mut parsed = stored.clone();
+        parsed.0[0].attributes.insert(
+            "points".into(),
+            AttributeValue::String("3".into()),
+        );
+        parsed.reconcile(&stored);
+        assert_eq!(parsed, stored);
+
+        parsed.0[0].attributes.insert(
+            "points".into(),
+            AttributeValue::String("5".into()),
+        );
+        parsed.reconcile(&stored);
+        assert_eq!(
+            parsed.0[0].attributes["points"],
+            AttributeValue::String("5".into())
+        );
+    }
+}
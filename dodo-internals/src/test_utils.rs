@@ -0,0 +1,25 @@
+//! Fixtures shared by the tests of this crate
+
+use chrono::NaiveDate;
+
+use crate::{Priority, Task, TaskSet};
+
+/// The day the tests take place on
+pub fn today() -> NaiveDate {
+    NaiveDate::from_ymd(2023, 1, 2)
+}
+
+/// A set of low priority tasks with the given names, created
+/// [today].
+pub fn tasks(names: &[&str]) -> TaskSet {
+    let mut set = TaskSet(vec![]);
+    for name in names {
+        set.push(Task::new(
+            (*name).into(),
+            Priority::Low,
+            today(),
+        ));
+    }
+
+    set
+}
@@ -0,0 +1,265 @@
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::{Status, Task, TaskSet};
+
+/// A stretch of time spent working on a task. Times keep the
+/// UTC offset they were recorded in, so that entries recorded in
+/// different timezones still add up.
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy,
+)]
+pub struct TimeEntry {
+    /// When work started
+    pub start: DateTime<FixedOffset>,
+    /// When work stopped, or `None` if it's still going on
+    pub end: Option<DateTime<FixedOffset>>,
+}
+
+impl TimeEntry {
+    /// Returns true if this entry's timer is still running.
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// How long this entry lasted, counting up to `now` if it's
+    /// still running.
+    pub fn duration(
+        &self,
+        now: DateTime<FixedOffset>,
+    ) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+impl Task {
+    /// The entry of this task's time log whose timer is still
+    /// running, if there's one.
+    pub fn running_timer(&self) -> Option<&TimeEntry> {
+        self.time_log.iter().find(|entry| entry.is_running())
+    }
+
+    /// Starts timing the work on this task, unless its timer is
+    /// already running. Tasks that weren't started yet become
+    /// [in progress](Status::InProgress).
+    ///
+    /// Returns false if the timer was already running.
+    pub fn start_timer(
+        &mut self,
+        now: DateTime<FixedOffset>,
+    ) -> bool {
+        if self.running_timer().is_some() {
+            return false;
+        }
+
+        self.time_log.push(TimeEntry {
+            start: now,
+            end: None,
+        });
+        if self.status == Status::Todo {
+            self.set_status(Status::InProgress, now);
+        }
+
+        true
+    }
+
+    /// Stops this task's timer, returning how long it ran for if
+    /// it was running.
+    pub fn stop_timer(
+        &mut self,
+        now: DateTime<FixedOffset>,
+    ) -> Option<Duration> {
+        let entry = self
+            .time_log
+            .iter_mut()
+            .find(|entry| entry.is_running())?;
+        entry.end = Some(now.max(entry.start));
+
+        Some(entry.duration(now))
+    }
+
+    /// The total time spent on this task, counting running
+    /// timers up to `now`.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::{Duration, NaiveDate, TimeZone}, Priority, Status, Task, Timezone};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut task = Task::new("Write report".into(), Priority::Low, today);
+    /// let at = |hour, minute| Timezone::utc().offset().ymd(2023, 1, 2).and_hms(hour, minute, 0);
+    ///
+    /// assert!(task.start_timer(at(9, 0)));
+    /// assert_eq!(task.status, Status::InProgress);
+    /// assert_eq!(task.stop_timer(at(9, 45)), Some(Duration::minutes(45)));
+    ///
+    /// task.start_timer(at(14, 0));
+    /// assert_eq!(task.time_spent(at(14, 30)), Duration::minutes(75));
+    /// ```
+    pub fn time_spent(
+        &self,
+        now: DateTime<FixedOffset>,
+    ) -> Duration {
+        self.time_log
+            .iter()
+            .map(|entry| entry.duration(now))
+            .fold(Duration::zero(), |total, spent| total + spent)
+    }
+}
+
+impl TaskSet {
+    /// The task whose timer is running, if there's one.
+    pub fn running_timer(&self) -> Option<&Task> {
+        self.0.iter().find(|task| task.running_timer().is_some())
+    }
+
+    /// Starts timing the work on the task with the given index,
+    /// stopping the timers of every other task first so that only
+    /// one runs at a time.
+    ///
+    /// Returns the indices of the tasks whose timers were stopped
+    /// along with how long they ran for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no task with the given index.
+    pub fn start_timer(
+        &mut self,
+        idx: usize,
+        now: DateTime<FixedOffset>,
+    ) -> Vec<(usize, Duration)> {
+        let stopped = self
+            .0
+            .iter_mut()
+            .filter(|task| task.idx != idx)
+            .filter_map(|task| {
+                task.stop_timer(now)
+                    .map(|spent| (task.idx, spent))
+            })
+            .collect();
+
+        self.get_mut(idx)
+            .expect("no task with the given index")
+            .start_timer(now);
+
+        stopped
+    }
+
+    /// Stops whichever timer is running, returning the index of
+    /// its task and how long it ran for.
+    pub fn stop_timer(
+        &mut self,
+        now: DateTime<FixedOffset>,
+    ) -> Option<(usize, Duration)> {
+        self.0.iter_mut().find_map(|task| {
+            task.stop_timer(now).map(|spent| (task.idx, spent))
+        })
+    }
+}
+
+/// (De)serializes durations as a number of seconds, since chrono
+/// can't do it by itself.
+pub(crate) mod seconds {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_seconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        i64::deserialize(deserializer).map(Duration::seconds)
+    }
+}
+
+/// Like [`seconds`], for optional durations.
+pub(crate) mod optional_seconds {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer
+                .serialize_some(&duration.num_seconds()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        let seconds = Option::<i64>::deserialize(deserializer)?;
+
+        Ok(seconds.map(Duration::seconds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, FixedOffset, TimeZone};
+
+    use crate::{test_utils::tasks, Status};
+
+    fn at(hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east(3600)
+            .ymd(2023, 1, 2)
+            .and_hms(hour, minute, 0)
+    }
+
+    #[test]
+    fn runs_one_timer_at_a_time() {
+        let mut set = tasks(&["Write report", "Review PR"]);
+
+        assert!(set.start_timer(1, at(9, 0)).is_empty());
+        assert_eq!(set.running_timer().unwrap().idx, 1);
+
+        let stopped = set.start_timer(2, at(9, 20));
+        assert_eq!(stopped, [(1, Duration::minutes(20))]);
+        assert_eq!(set.running_timer().unwrap().idx, 2);
+
+        // Starting a running timer again leaves it as it is
+        assert!(set.start_timer(2, at(9, 30)).is_empty());
+        assert_eq!(set.0[1].time_log.len(), 1);
+
+        assert_eq!(
+            set.stop_timer(at(10, 0)),
+            Some((2, Duration::minutes(40)))
+        );
+        assert_eq!(set.stop_timer(at(10, 5)), None);
+        assert!(set.running_timer().is_none());
+    }
+
+    #[test]
+    fn stops_timers_of_finished_tasks() {
+        let mut task = tasks(&["Write report"]).0.remove(0);
+        task.start_timer(at(9, 0));
+
+        task.set_status(Status::Done, at(11, 0));
+        assert!(task.running_timer().is_none());
+        assert_eq!(
+            task.time_spent(at(12, 0)),
+            Duration::hours(2)
+        );
+    }
+
+    #[test]
+    fn times_across_timezones() {
+        let mut task = tasks(&["Fly home"]).0.remove(0);
+        task.start_timer(at(9, 0));
+
+        // Landing an hour behind where the timer was started
+        let landed = FixedOffset::east(0)
+            .ymd(2023, 1, 2)
+            .and_hms(10, 0, 0);
+        assert_eq!(
+            task.stop_timer(landed),
+            Some(Duration::hours(2))
+        );
+    }
+}
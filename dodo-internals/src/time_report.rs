@@ -0,0 +1,317 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate};
+
+use crate::{
+    task_set::latest_versions, utils::format_duration, TaskId,
+    TaskSet, TimeEntry,
+};
+
+/// How the time spent on tasks compares to how long they were
+/// expected to take, as built by [`TimeReport::new`].
+///
+/// Displays as a table with a row per task followed by another
+/// with a row per day.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TimeReport {
+    /// Every task that was estimated or worked on
+    pub tasks: Vec<TaskTime>,
+    /// The days on which tasks were worked on or estimated tasks
+    /// were done, in order
+    pub days: Vec<DayTime>,
+}
+
+/// The time estimated for and spent on a single task.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TaskTime {
+    pub id: TaskId,
+    pub name: String,
+    pub estimate: Option<Duration>,
+    pub actual: Duration,
+}
+
+/// The time spent working on a given day, along with the
+/// estimates of the tasks that were done on it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DayTime {
+    pub date: NaiveDate,
+    pub estimate: Duration,
+    pub actual: Duration,
+}
+
+impl TimeReport {
+    /// Builds a report out of the task sets of many days, from
+    /// the oldest to the newest one, along with the day each one
+    /// is for.
+    ///
+    /// A task that shows up on many days is only counted once,
+    /// as it was on the latest of them. Timers that are still
+    /// running count up to `now`. Time is counted on the day it
+    /// was spent, while estimates are counted on the day their
+    /// task was done.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::{Duration, NaiveDate, TimeZone}, Priority, Status, Task, TaskSet, TimeReport, Timezone};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let at = |hour, minute| Timezone::utc().offset().ymd(2023, 1, 2).and_hms(hour, minute, 0);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Write report".into(), Priority::Low, today));
+    /// set.0[0].estimate = Some(Duration::hours(1));
+    /// set.0[0].start_timer(at(9, 0));
+    /// set.0[0].set_status(Status::Done, at(10, 30));
+    ///
+    /// let report = TimeReport::new([(today, &set)], at(12, 0));
+    /// assert_eq!(report.tasks[0].actual, Duration::minutes(90));
+    /// assert_eq!(report.days[0].date, today);
+    /// assert_eq!(report.days[0].estimate, Duration::hours(1));
+    /// assert_eq!(report.days[0].actual, Duration::minutes(90));
+    /// ```
+    pub fn new<'a>(
+        history: impl IntoIterator<Item = (NaiveDate, &'a TaskSet)>,
+        now: DateTime<FixedOffset>,
+    ) -> Self {
+        let latest: Vec<_> = latest_versions(history)
+            .into_iter()
+            .map(|(_, task)| task)
+            .collect();
+
+        let tasks: Vec<_> = latest
+            .iter()
+            .filter(|task| {
+                task.estimate.is_some()
+                    || !task.time_log.is_empty()
+            })
+            .map(|task| TaskTime {
+                id: task.id,
+                name: task.name.clone(),
+                estimate: task.estimate,
+                actual: task.time_spent(now),
+            })
+            .collect();
+
+        let mut days = BTreeMap::new();
+        let mut add = |date, estimate, actual| {
+            let day = days.entry(date).or_insert(DayTime {
+                date,
+                estimate: Duration::zero(),
+                actual: Duration::zero(),
+            });
+            day.estimate = day.estimate + estimate;
+            day.actual = day.actual + actual;
+        };
+        for task in &latest {
+            for entry in &task.time_log {
+                for (date, spent) in time_per_day(entry, now) {
+                    add(date, Duration::zero(), spent);
+                }
+            }
+
+            if let (Some(estimate), Some(completed_at)) =
+                (task.estimate, task.completed_at)
+            {
+                if task.is_done() {
+                    let date = completed_at.date().naive_local();
+                    add(date, estimate, Duration::zero());
+                }
+            }
+        }
+
+        Self {
+            tasks,
+            days: days.into_values().collect(),
+        }
+    }
+}
+
+/// Splits the time spent on `entry` between the days it spans, in
+/// the timezone its timer was started in.
+fn time_per_day(
+    entry: &TimeEntry,
+    now: DateTime<FixedOffset>,
+) -> Vec<(NaiveDate, Duration)> {
+    let mut start = entry.start;
+    let end =
+        entry.end.unwrap_or(now).with_timezone(start.offset());
+
+    let mut days = Vec::new();
+    while start < end {
+        let midnight = start.date().succ().and_hms(0, 0, 0);
+        let until = end.min(midnight);
+        days.push((start.date().naive_local(), until - start));
+        start = until;
+    }
+
+    days
+}
+
+/// Writes how much longer than expected something took, such as
+/// "+30m" or "-1h".
+fn difference(estimate: Duration, actual: Duration) -> String {
+    let difference = actual - estimate;
+    let sign = if difference > Duration::zero() {
+        "+"
+    } else {
+        ""
+    };
+
+    format!("{sign}{}", format_duration(difference))
+}
+
+impl Display for TimeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.tasks.is_empty() {
+            return writeln!(
+                f,
+                "No time was estimated or tracked yet"
+            );
+        }
+
+        writeln!(
+            f,
+            "{:>8}  {:>8}  {:>10}  Task",
+            "Estimate", "Actual", "Difference"
+        )?;
+        for task in &self.tasks {
+            let (estimate, difference) = match task.estimate {
+                Some(estimate) => (
+                    format_duration(estimate),
+                    difference(estimate, task.actual),
+                ),
+                None => ("-".into(), "-".into()),
+            };
+            writeln!(
+                f,
+                "{estimate:>8}  {:>8}  {difference:>10}  {}",
+                format_duration(task.actual),
+                task.name
+            )?;
+        }
+
+        if self.days.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<10}  {:>8}  {:>8}  {:>10}",
+            "Date", "Estimate", "Actual", "Difference"
+        )?;
+        for day in &self.days {
+            writeln!(
+                f,
+                "{:<10}  {:>8}  {:>8}  {:>10}",
+                day.date.to_string(),
+                format_duration(day.estimate),
+                format_duration(day.actual),
+                difference(day.estimate, day.actual)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, FixedOffset, TimeZone};
+
+    use super::TimeReport;
+    use crate::{Priority, Status, Task, TaskSet};
+
+    fn on(day: u32, hour: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east(0)
+            .ymd(2023, 1, day)
+            .and_hms(hour, 0, 0)
+    }
+
+    #[test]
+    fn counts_tasks_once_across_days() {
+        let mut monday = TaskSet(vec![]);
+        monday.push(Task::new(
+            "Write report".into(),
+            Priority::Low,
+            on(2, 0).date().naive_local(),
+        ));
+        monday.push(Task::new(
+            "Buy milk".into(),
+            Priority::Low,
+            on(2, 0).date().naive_local(),
+        ));
+        monday.0[0].estimate = Some(Duration::hours(3));
+        monday.0[0].start_timer(on(2, 9));
+        monday.0[0].stop_timer(on(2, 11));
+
+        // The report was carried over and finished on Tuesday
+        let mut tuesday = TaskSet(vec![monday.0[0].clone()]);
+        tuesday.0[0].start_timer(on(3, 9));
+        tuesday.0[0].set_status(Status::Done, on(3, 11));
+
+        let report = TimeReport::new(
+            [
+                (on(2, 0).date().naive_local(), &monday),
+                (on(3, 0).date().naive_local(), &tuesday),
+            ],
+            on(3, 12),
+        );
+
+        assert_eq!(report.tasks.len(), 1);
+        assert_eq!(report.tasks[0].actual, Duration::hours(4));
+        assert_eq!(report.days.len(), 2);
+        assert_eq!(
+            report.days[0].date,
+            on(2, 0).date().naive_local()
+        );
+        assert_eq!(report.days[0].estimate, Duration::zero());
+        assert_eq!(report.days[0].actual, Duration::hours(2));
+        assert_eq!(
+            report.days[1].date,
+            on(3, 0).date().naive_local()
+        );
+        assert_eq!(report.days[1].estimate, Duration::hours(3));
+        assert_eq!(report.days[1].actual, Duration::hours(2));
+
+        let text = report.to_string();
+        assert!(text
+            .contains("3h        4h         +1h  Write report"));
+        assert!(!text.contains("Buy milk"));
+    }
+
+    #[test]
+    fn splits_time_at_midnight() {
+        let mut set = TaskSet(vec![]);
+        set.push(Task::new(
+            "Deploy".into(),
+            Priority::Low,
+            on(2, 0).date().naive_local(),
+        ));
+        set.0[0].start_timer(on(2, 23));
+
+        let report = TimeReport::new(
+            [(on(2, 0).date().naive_local(), &set)],
+            on(3, 2),
+        );
+
+        let days: Vec<_> = report
+            .days
+            .iter()
+            .map(|day| (day.date, day.actual))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (
+                    on(2, 0).date().naive_local(),
+                    Duration::hours(1)
+                ),
+                (
+                    on(3, 0).date().naive_local(),
+                    Duration::hours(2)
+                ),
+            ]
+        );
+    }
+}
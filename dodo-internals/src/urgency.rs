@@ -0,0 +1,306 @@
+use std::{cmp::Ordering, mem};
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::{Priority, Query, Status, Task, TaskSet, Timezone};
+
+/// How much each trait of a task adds to its urgency, as
+/// computed by [`TaskSet::urgency`].
+///
+/// Each weight is the most that its trait can add, and negative
+/// weights make tasks less urgent instead.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct UrgencyWeights {
+    /// Added in full for high priority tasks, and in part for
+    /// medium and low priority ones
+    pub priority: f64,
+    /// Added in full once a task is overdue, and in part as its
+    /// deadline gets closer, starting two weeks before it
+    pub due: f64,
+    /// Added in full for tasks created a year ago or more, and
+    /// in part for newer ones
+    pub age: f64,
+    /// Added in full for tasks carried over ten days or more,
+    /// and in part for the ones carried over for less
+    pub carried_over: f64,
+    /// Added for tasks that can't move forward, be it because
+    /// of their status or because of their dependencies
+    pub blocked: f64,
+}
+
+impl Default for UrgencyWeights {
+    fn default() -> Self {
+        Self {
+            priority: 6.0,
+            due: 12.0,
+            age: 2.0,
+            carried_over: 4.0,
+            blocked: -5.0,
+        }
+    }
+}
+
+/// How close to its deadline a task is, from 0 for tasks with
+/// no deadline to 1 for overdue ones. Tasks due two weeks or
+/// more from now get 0.2.
+fn due_factor(task: &Task, now: DateTime<FixedOffset>) -> f64 {
+    const WINDOW: f64 = 14.0;
+
+    let deadline =
+        match task.deadline(Timezone::from(*now.offset())) {
+            Some(deadline) => deadline,
+            None => return 0.0,
+        };
+    let days_left =
+        (deadline - now).num_seconds() as f64 / 86_400.0;
+
+    1.0 - 0.8 * days_left.clamp(0.0, WINDOW) / WINDOW
+}
+
+impl TaskSet {
+    /// How urgent `task` is as of `now`, the higher the more
+    /// urgent, given how much each of its traits weighs.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::{NaiveDate, TimeZone}, Priority, Task, TaskSet, Timezone, UrgencyWeights};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Water the plants".into(), Priority::Low, today));
+    /// set.push(Task::new("Pay rent".into(), Priority::Low, today));
+    /// set.0[1].due_date = Some(today);
+    ///
+    /// let weights = UrgencyWeights::default();
+    /// let now = Timezone::utc().offset().ymd(2023, 1, 2).and_hms(9, 0, 0);
+    /// assert!(set.urgency(&set.0[1], &weights, now) > set.urgency(&set.0[0], &weights, now));
+    /// ```
+    pub fn urgency(
+        &self,
+        task: &Task,
+        weights: &UrgencyWeights,
+        now: DateTime<FixedOffset>,
+    ) -> f64 {
+        let priority = match task.priority {
+            Priority::High => 1.0,
+            Priority::Medium => 0.65,
+            Priority::Low => 0.3,
+        };
+        let today = now.date().naive_local();
+        let age = (today - task.creation_date).num_days() as f64
+            / 365.0;
+        let carried_over = f64::from(task.carried_over) / 10.0;
+        let is_blocked = matches!(
+            task.status,
+            Status::Blocked | Status::Waiting
+        ) || self.is_blocked(task);
+
+        weights.priority * priority
+            + weights.due * due_factor(task, now)
+            + weights.age * age.clamp(0.0, 1.0)
+            + weights.carried_over * carried_over.min(1.0)
+            + if is_blocked { weights.blocked } else { 0.0 }
+    }
+
+    /// Compares two tasks of this set by their
+    /// [urgency](TaskSet::urgency), falling back to [how
+    /// pressing](Task::cmp_pressing) they are when it's the
+    /// same.
+    fn cmp_urgency(
+        &self,
+        a: &Task,
+        b: &Task,
+        weights: &UrgencyWeights,
+        now: DateTime<FixedOffset>,
+    ) -> Ordering {
+        self.urgency(a, weights, now)
+            .total_cmp(&self.urgency(b, weights, now))
+            .then_with(|| a.cmp_pressing(b))
+    }
+
+    /// Sorts the tasks of this set from the most to the least
+    /// urgent one, and renumbers them to match.
+    pub fn sort_by_urgency(
+        &mut self,
+        weights: &UrgencyWeights,
+        now: DateTime<FixedOffset>,
+    ) {
+        // Scoring a task looks its dependencies up, so each one
+        // is only scored once
+        let scores: Vec<_> = self
+            .0
+            .iter()
+            .map(|task| self.urgency(task, weights, now))
+            .collect();
+        let mut scored: Vec<_> = mem::take(&mut self.0)
+            .into_iter()
+            .zip(scores)
+            .collect();
+        scored.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| b.cmp_pressing(a))
+        });
+
+        self.0 =
+            scored.into_iter().map(|(task, _)| task).collect();
+        self.renumber();
+    }
+
+    /// The most urgent task matching `query` that can be worked
+    /// on right away, meaning that it's open, not snoozed and
+    /// not blocked.
+    pub fn most_urgent(
+        &self,
+        query: &Query,
+        weights: &UrgencyWeights,
+        now: DateTime<FixedOffset>,
+    ) -> Option<&Task> {
+        let today = now.date().naive_local();
+
+        self.0
+            .iter()
+            .filter(|task| {
+                query.matches(task, today)
+                    && matches!(
+                        task.status,
+                        Status::Todo | Status::InProgress
+                    )
+                    && !task.is_snoozed(today)
+                    && !self.is_blocked(task)
+            })
+            .max_by(|a, b| self.cmp_urgency(a, b, weights, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+
+    use super::UrgencyWeights;
+    use crate::{
+        Priority, Query, Status, Task, TaskSet, Timezone,
+    };
+
+    fn now() -> DateTime<FixedOffset> {
+        Timezone::utc()
+            .offset()
+            .ymd(2023, 1, 10)
+            .and_hms(9, 0, 0)
+    }
+
+    fn tasks() -> TaskSet {
+        let today = NaiveDate::from_ymd(2023, 1, 10);
+        let mut set = TaskSet(vec![]);
+        for (name, priority) in [
+            ("Water the plants", Priority::Low),
+            ("Pay rent", Priority::Low),
+            ("Deploy", Priority::High),
+            ("Write report", Priority::High),
+        ] {
+            set.push(Task::new(name.into(), priority, today));
+        }
+
+        set
+    }
+
+    #[test]
+    fn weighs_every_trait() {
+        let mut set = tasks();
+        let weights = UrgencyWeights::default();
+        let urgency = |set: &TaskSet, idx| {
+            set.urgency(&set.0[idx], &weights, now())
+        };
+
+        let before = urgency(&set, 0);
+        set.0[0].carried_over = 5;
+        assert!(urgency(&set, 0) > before);
+
+        let before = urgency(&set, 0);
+        set.0[0].creation_date = NaiveDate::from_ymd(2022, 7, 1);
+        assert!(urgency(&set, 0) > before);
+
+        // Overdue tasks beat ones that are due soon, which beat
+        // ones that are due later
+        set.0[1].due_date =
+            Some(NaiveDate::from_ymd(2023, 1, 20));
+        let later = urgency(&set, 1);
+        set.0[1].due_date =
+            Some(NaiveDate::from_ymd(2023, 1, 12));
+        let soon = urgency(&set, 1);
+        set.0[1].due_date =
+            Some(NaiveDate::from_ymd(2023, 1, 5));
+        assert!(urgency(&set, 1) > soon && soon > later);
+
+        let before = urgency(&set, 2);
+        set.0[2].status = Status::Waiting;
+        assert!(urgency(&set, 2) < before);
+
+        let only_priority = UrgencyWeights {
+            priority: 1.0,
+            due: 0.0,
+            age: 0.0,
+            carried_over: 0.0,
+            blocked: 0.0,
+        };
+        assert!(
+            set.urgency(&set.0[3], &only_priority, now())
+                > set.urgency(&set.0[1], &only_priority, now())
+        );
+    }
+
+    #[test]
+    fn picks_the_most_urgent_actionable_task() {
+        let mut set = tasks();
+        let weights = UrgencyWeights::default();
+
+        // "Deploy" waits on "Write report", which is snoozed
+        let report = set.0[3].id;
+        set.0[2].depends_on.push(report);
+        set.0[3].wait_until =
+            Some(NaiveDate::from_ymd(2023, 1, 11));
+        set.0[1].due_date =
+            Some(NaiveDate::from_ymd(2023, 1, 5));
+
+        let next = set
+            .most_urgent(&Query::default(), &weights, now())
+            .unwrap();
+        assert_eq!(next.name, "Pay rent");
+
+        let errands = "!name~rent".parse().unwrap();
+        let next = set.most_urgent(&errands, &weights, now());
+        assert_eq!(next.unwrap().name, "Water the plants");
+
+        set.0[1].status = Status::Done;
+        let next = set
+            .most_urgent(&Query::default(), &weights, now())
+            .unwrap();
+        assert_eq!(next.name, "Water the plants");
+    }
+
+    #[test]
+    fn sorts_by_urgency() {
+        let mut set = tasks();
+        set.0[1].due_date =
+            Some(NaiveDate::from_ymd(2023, 1, 5));
+        set.0[3].carried_over = 1;
+
+        set.sort_by_urgency(&UrgencyWeights::default(), now());
+
+        let names: Vec<_> = set
+            .0
+            .iter()
+            .map(|task| task.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "Pay rent",
+                "Write report",
+                "Deploy",
+                "Water the plants"
+            ]
+        );
+        assert_eq!(set.0[0].idx, 1);
+    }
+}
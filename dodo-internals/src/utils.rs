@@ -1,5 +1,161 @@
-use chrono::{Local, NaiveDate};
+use std::borrow::Cow;
 
-pub fn today() -> NaiveDate {
-    Local::today().naive_local()
+use chrono::Duration;
+
+/// The most days a date given relative to another one may be
+/// apart from it, which keeps it within what chrono can handle.
+pub const MAX_DAY_OFFSET: i64 = 100_000;
+
+/// The characters that have to be escaped with a backslash in
+/// the text representation of tasks, along with what they're
+/// written as after the backslash.
+const ESCAPES: [(char, char); 5] = [
+    ('\\', '\\'),
+    ('[', '['),
+    (']', ']'),
+    ('\n', 'n'),
+    ('\r', 'r'),
+];
+
+/// Escapes the characters of `text` that would otherwise be
+/// mistaken for part of the syntax of the text representation of
+/// tasks, such as brackets and line breaks.
+///
+/// ```rust
+/// use dodo_internals::utils::{escape, unescape};
+/// let name = "Review PR [#42]";
+/// assert_eq!(escape(name), r"Review PR \[#42\]");
+/// assert_eq!(unescape(&escape(name)), name);
+/// ```
+pub fn escape(text: &str) -> Cow<'_, str> {
+    let needs_escaping = |ch: char| {
+        ESCAPES.iter().any(|&(escaped, _)| escaped == ch)
+    };
+
+    if !text.contains(needs_escaping) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 2);
+    for ch in text.chars() {
+        match ESCAPES
+            .iter()
+            .find(|&&(original, _)| original == ch)
+        {
+            Some(&(_, code)) => {
+                escaped.push('\\');
+                escaped.push(code);
+            }
+            None => escaped.push(ch),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+/// Reverts what [`escape`] does.
+///
+/// Backslashes not followed by a known escape code are kept
+/// as-is.
+pub fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('\\') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        let escaped = chars.peek().and_then(|&next| {
+            ESCAPES.iter().find(|&&(_, code)| code == next)
+        });
+
+        match (ch, escaped) {
+            ('\\', Some(&(original, _))) => {
+                unescaped.push(original);
+                chars.next();
+            }
+            _ => unescaped.push(ch),
+        }
+    }
+
+    Cow::Owned(unescaped)
+}
+
+/// Returns true if the given character may be part of a name,
+/// such as the name of a tag, project or context, meaning that
+/// it's neither whitespace nor a bracket.
+pub fn is_word_char(ch: char) -> bool {
+    !ch.is_whitespace() && ch != '[' && ch != ']'
+}
+
+/// Validates the name of a tag, project or context, which must
+/// be made out of [word characters](is_word_char).
+///
+/// ```rust
+/// use dodo_internals::utils::parse_word;
+/// assert_eq!(parse_word("errands"), Ok("errands".into()));
+/// assert!(parse_word("two words").is_err());
+/// ```
+pub fn parse_word(input: &str) -> Result<String, String> {
+    if input.is_empty() || !input.chars().all(is_word_char) {
+        return Err(format!(
+            "invalid name '{input}', expected a name without \
+             whitespace or brackets"
+        ));
+    }
+
+    Ok(input.to_owned())
+}
+
+/// Writes a duration in hours and minutes, leaving out the hours
+/// when there are none, such as "1h30m", "2h" or "-45m".
+///
+/// ```rust
+/// use dodo_internals::{chrono::Duration, utils::{format_duration, parse_duration}};
+/// assert_eq!(format_duration(Duration::minutes(90)), "1h30m");
+/// assert_eq!(format_duration(Duration::minutes(-45)), "-45m");
+/// assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+/// assert_eq!(parse_duration("2h"), Some(Duration::hours(2)));
+/// assert_eq!(parse_duration("1h30"), None);
+/// ```
+pub fn format_duration(duration: Duration) -> String {
+    let sign =
+        if duration < Duration::zero() { "-" } else { "" };
+    let minutes = duration.num_minutes().abs();
+
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{sign}{minutes}m"),
+        (hours, 0) => format!("{sign}{hours}h"),
+        (hours, minutes) => format!("{sign}{hours}h{minutes}m"),
+    }
+}
+
+/// Reverts what [`format_duration`] does for durations that
+/// aren't negative. Minutes may go past 59, as in "90m".
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let number = |digits: &str| -> Option<i64> {
+        if digits.is_empty()
+            || !digits.chars().all(|ch| ch.is_ascii_digit())
+        {
+            return None;
+        }
+        digits.parse().ok()
+    };
+
+    let (hours, minutes) = match text.split_once('h') {
+        Some((hours, "")) => (number(hours)?, 0),
+        Some((hours, minutes)) => (
+            number(hours)?,
+            number(minutes.strip_suffix('m')?)?,
+        ),
+        None => (0, number(text.strip_suffix('m')?)?),
+    };
+
+    // Durations are stored in milliseconds
+    hours
+        .checked_mul(60)?
+        .checked_add(minutes)
+        .filter(|&minutes| minutes <= i64::MAX / 60_000)
+        .map(Duration::minutes)
 }
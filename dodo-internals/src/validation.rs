@@ -0,0 +1,306 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use chrono::NaiveDate;
+
+use crate::{Checklist, Cycle, TaskSet};
+
+/// Something wrong with a task set, as found by
+/// [`TaskSet::validate`].
+///
+/// Tasks are referred to by their index.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ValidationIssue {
+    /// An index that more than one task has
+    DuplicateIndex(usize),
+    /// An index between 1 and the number of tasks that no task
+    /// has
+    MissingIndex(usize),
+    /// A task whose name is empty
+    EmptyName { idx: usize },
+    /// A task whose name starts or ends with whitespace
+    UntrimmedName { idx: usize },
+    /// A task that was due before it was even created
+    DueBeforeCreation {
+        idx: usize,
+        due_date: NaiveDate,
+        creation_date: NaiveDate,
+    },
+    /// Tasks that depend on each other in a loop
+    DependencyCycle(Cycle),
+    /// A checklist with more than one checkbox with the same
+    /// description, which may be nested in another checkbox
+    DuplicateCheckbox { idx: usize, description: String },
+}
+
+impl ValidationIssue {
+    /// Returns true if [`TaskSet::fix`] can fix this issue.
+    pub fn is_fixable(&self) -> bool {
+        matches!(
+            self,
+            ValidationIssue::DuplicateIndex(_)
+                | ValidationIssue::MissingIndex(_)
+                | ValidationIssue::UntrimmedName { .. }
+        )
+    }
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::DuplicateIndex(idx) => {
+                write!(f, "more than one task is number {idx}")
+            }
+            ValidationIssue::MissingIndex(idx) => {
+                write!(f, "no task is number {idx}")
+            }
+            ValidationIssue::EmptyName { idx } => {
+                write!(f, "task {idx} has no name")
+            }
+            ValidationIssue::UntrimmedName { idx } => write!(
+                f,
+                "the name of task {idx} starts or ends with \
+                 whitespace"
+            ),
+            ValidationIssue::DueBeforeCreation {
+                idx,
+                due_date,
+                creation_date,
+            } => write!(
+                f,
+                "task {idx} is due on {due_date}, before it was \
+                 created on {creation_date}"
+            ),
+            ValidationIssue::DependencyCycle(cycle) => write!(
+                f,
+                "some tasks depend on each other in a loop and \
+                 can never start: {cycle}"
+            ),
+            ValidationIssue::DuplicateCheckbox {
+                idx,
+                description,
+            } => write!(
+                f,
+                "task {idx} has more than one '{description}' \
+                 checkbox"
+            ),
+        }
+    }
+}
+
+/// The descriptions that show up more than once in `checklist`
+/// or in any of the checklists nested in it.
+fn duplicate_descriptions(checklist: &Checklist) -> Vec<String> {
+    let mut counts = BTreeMap::new();
+    let mut duplicates = Vec::new();
+
+    for checkbox in checklist.iter() {
+        *counts.entry(checkbox.description()).or_insert(0) += 1;
+        duplicates
+            .extend(duplicate_descriptions(checkbox.children()));
+    }
+
+    duplicates.extend(
+        counts
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .map(|(description, _)| description.to_owned()),
+    );
+
+    duplicates
+}
+
+impl TaskSet {
+    /// Lists everything that's wrong with this set, such as
+    /// repeated indices or tasks without a name.
+    ///
+    /// ```rust
+    /// # use dodo_internals::{chrono::NaiveDate, Priority, Task, TaskSet, ValidationIssue};
+    /// let today = NaiveDate::from_ymd(2023, 1, 2);
+    /// let mut set = TaskSet(vec![]);
+    /// set.push(Task::new("Buy milk".into(), Priority::Low, today));
+    /// set.push(Task::new(" Walk the dog".into(), Priority::Low, today));
+    /// set.0[1].idx = 1;
+    ///
+    /// assert_eq!(
+    ///     set.validate(),
+    ///     [
+    ///         ValidationIssue::DuplicateIndex(1),
+    ///         ValidationIssue::MissingIndex(2),
+    ///         ValidationIssue::UntrimmedName { idx: 1 },
+    ///     ]
+    /// );
+    ///
+    /// set.fix();
+    /// assert!(set.validate().is_empty());
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        let mut counts = BTreeMap::new();
+        for task in &self.0 {
+            *counts.entry(task.idx).or_insert(0) += 1;
+        }
+        issues.extend(
+            counts.iter().filter(|&(_, &count)| count > 1).map(
+                |(&idx, _)| ValidationIssue::DuplicateIndex(idx),
+            ),
+        );
+        issues.extend(
+            (1..=self.0.len())
+                .filter(|idx| !counts.contains_key(idx))
+                .map(ValidationIssue::MissingIndex),
+        );
+
+        for task in &self.0 {
+            let idx = task.idx;
+
+            if task.name.trim().is_empty() {
+                issues.push(ValidationIssue::EmptyName { idx });
+            } else if task.name.trim() != task.name {
+                issues.push(ValidationIssue::UntrimmedName {
+                    idx,
+                });
+            }
+
+            if let Some(due_date) =
+                task.due_date.filter(|&due_date| {
+                    due_date < task.creation_date
+                })
+            {
+                issues.push(
+                    ValidationIssue::DueBeforeCreation {
+                        idx,
+                        due_date,
+                        creation_date: task.creation_date,
+                    },
+                );
+            }
+
+            issues.extend(
+                duplicate_descriptions(&task.checklist)
+                    .into_iter()
+                    .map(|description| {
+                        ValidationIssue::DuplicateCheckbox {
+                            idx,
+                            description,
+                        }
+                    }),
+            );
+        }
+
+        if let Some(cycle) = self.find_cycle() {
+            issues.push(ValidationIssue::DependencyCycle(cycle));
+        }
+
+        issues
+    }
+
+    /// Fixes the [fixable](ValidationIssue::is_fixable) issues
+    /// of this set, by renumbering its tasks and trimming their
+    /// names. Returns the issues that were fixed.
+    pub fn fix(&mut self) -> Vec<ValidationIssue> {
+        let fixed: Vec<_> = self
+            .validate()
+            .into_iter()
+            .filter(ValidationIssue::is_fixable)
+            .collect();
+
+        self.renumber();
+        for task in &mut self.0 {
+            if task.name.trim() != task.name {
+                task.name = task.name.trim().to_owned();
+            }
+        }
+
+        fixed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::ValidationIssue;
+    use crate::{
+        test_utils::{tasks, today},
+        Checkbox, Checklist,
+    };
+
+    #[test]
+    fn reports_every_issue() {
+        let mut set = tasks(&["Pay rent", "", "Pack", "Unpack"]);
+        set.0[0].due_date =
+            Some(NaiveDate::from_ymd(2022, 12, 31));
+        set.0[2].checklist = Checklist::with_checkboxes(vec![
+            Checkbox::with_description("Socks".into()),
+            Checkbox::with_description("Shirts".into())
+                .with_children(Checklist::with_checkboxes(
+                    vec![
+                        Checkbox::with_description(
+                            "Blue".into(),
+                        ),
+                        Checkbox::with_description(
+                            "Blue".into(),
+                        ),
+                    ],
+                )),
+            Checkbox::with_description("Socks".into()),
+        ]);
+        let (pack, unpack) = (set.0[2].id, set.0[3].id);
+        set.0[2].depends_on.push(unpack);
+        set.0[3].depends_on.push(pack);
+
+        let issues = set.validate();
+
+        assert_eq!(
+            issues[..4],
+            [
+                ValidationIssue::DueBeforeCreation {
+                    idx: 1,
+                    due_date: NaiveDate::from_ymd(2022, 12, 31),
+                    creation_date: today(),
+                },
+                ValidationIssue::EmptyName { idx: 2 },
+                ValidationIssue::DuplicateCheckbox {
+                    idx: 3,
+                    description: "Blue".into()
+                },
+                ValidationIssue::DuplicateCheckbox {
+                    idx: 3,
+                    description: "Socks".into()
+                },
+            ]
+        );
+        assert!(matches!(
+            &issues[4..],
+            [ValidationIssue::DependencyCycle(_)]
+        ));
+        assert!(!issues.iter().any(ValidationIssue::is_fixable));
+    }
+
+    #[test]
+    fn fixes_indices_and_names() {
+        let mut set =
+            tasks(&["Buy milk ", "Walk the dog", "Nap"]);
+        set.0[1].idx = 5;
+        set.0[2].idx = 5;
+
+        let fixed = set.fix();
+
+        assert_eq!(
+            fixed,
+            [
+                ValidationIssue::DuplicateIndex(5),
+                ValidationIssue::MissingIndex(2),
+                ValidationIssue::MissingIndex(3),
+                ValidationIssue::UntrimmedName { idx: 1 },
+            ]
+        );
+        assert!(set.validate().is_empty());
+        assert_eq!(set.0[0].name, "Buy milk");
+        assert_eq!(set.0[2].idx, 3);
+    }
+}